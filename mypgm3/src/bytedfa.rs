@@ -0,0 +1,135 @@
+//! CSIS-616 - Program #3
+//!
+//! Definition and methods associated with a DFA that reads bytes instead
+//! of characters.
+//!
+//! Each symbol of the alphabet is spelled out as the path of its UTF-8
+//! bytes. The states of the yaml DFA come first and keep their numbers;
+//! the states after them are partway through a symbol. A byte with no
+//! transition, including every byte that is not valid UTF-8, rejects the
+//! input.
+
+use crate::dfa;
+
+// *********************************************************************
+/// # Byte DFA Structure
+#[derive(Debug)]
+pub struct ByteDFA {
+
+    /// Number of states of the yaml DFA, which come first (0 relative)
+    pub states: usize,
+
+    /// State number (0 relative) for the start state
+    pub start: usize,
+
+    /// Is each state an accept state, never true partway through a symbol
+    pub accept: Vec<bool>,
+
+    /// Matrix of transitions, rows are states, columns the 256 byte values
+    pub transitions: Vec<Vec<Option<usize>>>,
+}
+
+// *********************************************************************
+/// Implement the methods of the ByteDFA structure
+impl ByteDFA {
+
+    /// Spell out the transitions of a validated DFA as UTF-8 bytes
+    pub fn new_from_dfa(dfa: &dfa::DFA) -> ByteDFA {
+
+        let states = dfa.transitions.len();
+        let mut bytes = ByteDFA{states,
+                                start: dfa.start - 1,
+                                accept: vec![false; states],
+                                transitions: vec![vec![None; 256]; states]};
+
+        for state in dfa.accept.iter() {
+            bytes.accept[*state - 1] = true;
+        }
+
+        for (rnum, row) in dfa.transitions.iter().enumerate() {
+            for (symbol, state) in dfa.alphabet.iter().zip(row.iter()) {
+
+                let mut buffer = [0u8; 4];
+                let encoded = symbol.encode_utf8(&mut buffer).as_bytes();
+                let (last, first) = encoded.split_last().unwrap();
+
+                // Follow or make the states partway through the symbol
+                let mut current = rnum;
+                for b in first {
+                    current = match bytes.transitions[current][*b as usize] {
+                        Some(next) => next,
+                        None => {
+                            bytes.transitions.push(vec![None; 256]);
+                            bytes.accept.push(false);
+                            bytes.transitions[current][*b as usize] = Some(bytes.transitions.len() - 1);
+                            bytes.transitions.len() - 1
+                        }
+                    };
+                }
+
+                bytes.transitions[current][*last as usize] = Some(*state - 1);
+            }
+        }
+
+        bytes
+    }
+
+    //Process the input bytes, print out the processing steps, print whether it accepts or rejects the string
+    //Return whether it was accepted
+    //The bytes are read as UTF-8, a symbol outside the alphabet or bytes
+    //that are not valid UTF-8 give an error naming where they are
+    pub fn process(&self, input: &[u8]) -> Result<bool, String> {
+        //Get the current state, which is the start state (0 relative).
+        //from is the state the symbol being read started in, and begin its first byte
+        let mut current = self.start;
+        let mut from = current;
+        let mut begin = 0;
+
+        //Iterate through the input bytes, following the transition of each one
+        //A byte without a transition rejects the input with an error
+        //Once the bytes of a whole symbol are read, print out the transition
+        for (i, b) in input.iter().enumerate() {
+            current = match self.transitions[current][*b as usize] {
+                Some(next) => next,
+                None => return Err(ByteDFA::rejected(input, begin))
+            };
+
+            if current < self.states {
+                let symbol = std::str::from_utf8(&input[begin..=i]).unwrap();
+                println!{"𝛿(q{},{}) → q{}", from + 1, symbol, current + 1};
+                from = current;
+                begin = i + 1;
+            }
+        }
+
+        //The input ended partway through a symbol
+        if current >= self.states {
+            return Err(ByteDFA::rejected(input, begin));
+        }
+
+        //If the final state is in an accept state, accept. Otherwise, reject.
+        let accepted = self.accept[current];
+        if accepted {
+            println!("{} is accepted.", String::from_utf8_lossy(input));
+        }
+        else {
+            println!("{} is rejected.", String::from_utf8_lossy(input));
+        }
+        Ok(accepted)
+    }
+
+    //The error for input that could not be read from byte offset `begin`, naming the
+    //symbol starting there, or the offset when no valid UTF-8 starts there
+    fn rejected(input: &[u8], begin: usize) -> String {
+        let valid = match std::str::from_utf8(&input[begin..]) {
+            Ok(text) => text,
+            Err(e) => std::str::from_utf8(&input[begin..begin + e.valid_up_to()]).unwrap()
+        };
+
+        match valid.chars().next() {
+            Some(symbol) => format!("{} is not a valid symbol in the language. Rejected.", symbol),
+            None => format!("Invalid UTF-8 at byte {}. Rejected.", begin)
+        }
+    }
+
+}
@@ -0,0 +1,60 @@
+//! CSIS-616 - Program #3
+//!
+//! Generate a standalone Rust module from a validated DFA.
+//!
+//! The module has no dependencies. It holds the DFA as constant tables laid
+//! out like the yaml file, the alphabet, start state, accept states and
+//! transition matrix with states 1 relative, and a single entry point,
+//! `pub fn accepts(input: &str) -> bool`, that walks the transition matrix.
+//! A character outside the alphabet rejects the input, the same as
+//! `DFA::process`.
+
+use std::fmt::Write;
+
+use crate::dfa;
+
+// *********************************************************************
+/// Return the Rust source of a module accepting the same strings as the
+/// DFA, `source` names where the DFA came from in the module comment
+pub fn rust_source(dfa: &dfa::DFA, source: &str) -> String {
+
+    let mut code = String::new();
+    let symbols = dfa.alphabet.len();
+
+    writeln!(code, "//! Generated from {}, do not edit.", source).unwrap();
+    writeln!(code).unwrap();
+
+    writeln!(code, "/// The set of characters comprising the alphabet").unwrap();
+    writeln!(code, "const ALPHABET: [char; {}] = {:?};", symbols, dfa.alphabet).unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "/// State number (1 relative) for the start state").unwrap();
+    writeln!(code, "const START: usize = {};", dfa.start).unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "/// Set of accept states (1 relative)").unwrap();
+    writeln!(code, "const ACCEPT: [usize; {}] = {:?};", dfa.accept.len(), dfa.accept).unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "/// Matrix of transitions, rows are states, columns characters in the alphabet").unwrap();
+    writeln!(code, "const TRANSITIONS: [[usize; {}]; {}] = [", symbols, dfa.transitions.len()).unwrap();
+    for row in dfa.transitions.iter() {
+        writeln!(code, "    {:?},", row).unwrap();
+    }
+    writeln!(code, "];").unwrap();
+    writeln!(code).unwrap();
+
+    writeln!(code, "/// Does the DFA accept the whole input").unwrap();
+    writeln!(code, "pub fn accepts(input: &str) -> bool {{").unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "    let mut state = START;").unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "    for c in input.chars() {{").unwrap();
+    writeln!(code, "        match ALPHABET.iter().position(|a| *a == c) {{").unwrap();
+    writeln!(code, "            Some(column) => state = TRANSITIONS[state - 1][column],").unwrap();
+    writeln!(code, "            None => return false").unwrap();
+    writeln!(code, "        }}").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "    ACCEPT.contains(&state)").unwrap();
+    writeln!(code, "}}").unwrap();
+
+    code
+}
@@ -10,15 +10,18 @@
 //! 
 //! Ralph W. Crosby PhD.
 //! 
-use serde::{Deserialize};
+use serde::{Deserialize, Serialize};
+
+use crate::bytedfa;
 
 // *********************************************************************
 /// # Deterministic Finite Automata Structure
 /// 
 /// Create a structure that the YAML files will be deserialized into.
-/// Note the use of the `Deserialize` trait
+/// Note the use of the `Deserialize` trait, and `Serialize` to write
+/// the DFAs the program builds as YAML
 /// 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DFA {
 
     /// The set of characters comprising the alphabet
@@ -43,7 +46,6 @@ impl DFA {
     /// 
     /// Load the .yaml file specified into a DFA structure
     /// on the heap and return a point to it via a Box.
-
     pub fn new_from_file(filename: &str) -> Box<DFA> {
 
         let f = std::fs::File::open(filename)
@@ -74,7 +76,7 @@ impl DFA {
         for (rnum, row) in self.transitions.iter().enumerate() {
             for (cnum, state) in row.iter().enumerate() {
 
-                if *state as usize >  self.transitions.len() {
+                if *state >  self.transitions.len() {
                     return Err(format!("Invalid transition state({}) in row {}, column {}",
                                         state, rnum + 1, cnum + 1 ))
                 }
@@ -83,12 +85,12 @@ impl DFA {
         }
 
         // The start and accept states must be valid
-        if self.start as usize > self.transitions.len() {
+        if self.start > self.transitions.len() {
             return Err(format!("Start state({}), is not valid", self.start))
         }
 
         for acc_state in self.accept.iter() {
-            if *acc_state as usize  > self.transitions.len() {
+            if *acc_state  > self.transitions.len() {
                 return Err(format!("Accept state({}), is not valid", acc_state))
             }
         }
@@ -96,46 +98,12 @@ impl DFA {
         Ok(self)
     }
 
-    //Process the input string, print out the processing steps, print whether it accepts or rejects the string
-    pub fn process(&self, inputstring: &str) -> Result<&DFA, String> {
-        //Get the current state, which is the start state. Set next as current for future use. 
-        let mut current = self.start;
-        let mut next = current;
-
-        //Iterate through the input string
-        //Check each character to make ssure if is in the alphabet
-        //Set the next state by iterating over the current transition, getting the input and next state
-        //If the input matches the symbol, set the next state based on the if statement results
-        //Print out the transition process
-        //Set current as next, then repeat
-        for symbol in inputstring.chars() {
-            if !self.alphabet.contains(&symbol) {
-                return Err(format!("{} is not a valid symbol in the language. Rejected.", symbol))
-            }
-
-            //next state selection
-            for (input, state) in self.transitions[current-1].iter().enumerate() {
-                //check which symbol matches the alphabet, 
-                //then set next as the state if it matches
-                if self.alphabet[input] == symbol {
-                    next = *state;
-                }
-            }
-
-            println!{"𝛿(q{},{}) → q{}", current, symbol, next};
-
-            //set current state as next state
-            current = next;
-        }
-        
-        //If the final state is in an accept state, accept. Otherwise, reject.
-        if self.accept.contains(&current) {
-            println!("{} is accepted.", inputstring);
-        }
-        else {
-            println!("{} is rejected.", inputstring);
-        }
-        Ok(self)
+    //Process the input bytes, print out the processing steps, print whether it accepts or rejects the string
+    //Return whether it was accepted
+    //The byte DFA reading them is built on every call, build it once with ByteDFA::new_from_dfa
+    //to process many inputs
+    pub fn process(&self, input: &[u8]) -> Result<bool, String> {
+        bytedfa::ByteDFA::new_from_dfa(self).process(input)
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,392 @@
+//! CSIS-616 - Program #3
+//!
+//! Convert a DFA back into a regular expression by state elimination.
+//!
+//! The DFA becomes a generalized NFA whose edges are labelled with regexes:
+//! a new start state with an ε edge to the old one, a new accept state with
+//! ε edges from the old accept states, and parallel transitions merged into
+//! one alternation. The old states are then removed one at a time, each
+//! path through the removed state `q` becoming the edge `in q-loop* out`.
+//! Once only the new start and accept states are left, the edge between
+//! them is the regex.
+//!
+//! The order states are removed in does not change the language but can
+//! change the length of the regex a great deal, so it is chosen by one of
+//! the `Order` heuristics. The regex is written in the syntax read by
+//! project1.
+
+use std::fmt;
+
+use crate::dfa;
+
+// *********************************************************************
+/// # Regular Expression Structure
+///
+/// Built only through `concat`, `alternation` and `star`, which simplify
+/// as they go, so `Empty` never appears inside another node.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Regex {
+
+    /// Matches nothing
+    Empty,
+
+    /// Matches only the empty string
+    Epsilon,
+
+    /// Matches one symbol of the alphabet
+    Symbol(char),
+
+    /// Matches the items one after the other, at least two
+    Concat(Vec<Regex>),
+
+    /// Matches any one of the items, at least two, sorted and distinct
+    Alternation(Vec<Regex>),
+
+    /// Matches the item repeated zero or more times
+    Star(Box<Regex>),
+}
+
+// *********************************************************************
+/// Implement the methods of the Regex structure
+impl Regex {
+
+    /// Number of symbols written in the regex
+    pub fn size(&self) -> usize {
+        match self {
+            Regex::Empty | Regex::Epsilon => 0,
+            Regex::Symbol(_) => 1,
+            Regex::Concat(items) | Regex::Alternation(items) => items.iter().map(|r| r.size()).sum(),
+            Regex::Star(inner) => inner.size()
+        }
+    }
+
+    /// Does the regex match the empty string
+    fn nullable(&self) -> bool {
+        match self {
+            Regex::Empty | Regex::Symbol(_) => false,
+            Regex::Epsilon | Regex::Star(_) => true,
+            Regex::Concat(items) => items.iter().all(|r| r.nullable()),
+            Regex::Alternation(items) => items.iter().any(|r| r.nullable())
+        }
+    }
+
+    /// Write the regex where an operator binding at least as tight as
+    /// `level` surrounds it: 0 alternation, 1 concatenation, 2 repetition
+    fn write(&self, f: &mut fmt::Formatter<'_>, level: u8) -> fmt::Result {
+        match self {
+
+            // A class with every character left out
+            Regex::Empty => write!(f, "[^\\u{{0}}-\\u{{10FFFF}}]"),
+            Regex::Epsilon => write!(f, "()"),
+            Regex::Symbol(c) => write_symbol(f, *c),
+            Regex::Concat(items) => {
+                if level > 1 {
+                    write!(f, "(")?;
+                }
+                for item in items {
+                    item.write(f, 1)?;
+                }
+                if level > 1 {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            },
+            Regex::Alternation(items) => {
+
+                // The single symbols are written as one bracket class, and
+                // ε as a ? after the rest
+                let optional = items.contains(&Regex::Epsilon);
+                let symbols: Vec<char> = items.iter()
+                                              .filter_map(|r| match r { Regex::Symbol(c) => Some(*c), _ => None })
+                                              .collect();
+                let others: Vec<&Regex> = items.iter()
+                                               .filter(|r| !matches!(r, Regex::Epsilon | Regex::Symbol(_)))
+                                               .collect();
+
+                let alternatives = usize::from(!symbols.is_empty()) + others.len();
+                let parens = alternatives > 1 && (optional || level > 0);
+                let inner = if alternatives == 1 && optional { 2 } else { 0 };
+
+                if parens {
+                    write!(f, "(")?;
+                }
+                match symbols.len() {
+                    0 => (),
+                    1 => write_symbol(f, symbols[0])?,
+                    _ => {
+                        write!(f, "[")?;
+                        for c in symbols.iter() {
+                            write_symbol(f, *c)?;
+                        }
+                        write!(f, "]")?;
+                    }
+                }
+                for (n, item) in others.iter().enumerate() {
+                    if n > 0 || !symbols.is_empty() {
+                        write!(f, "|")?;
+                    }
+                    item.write(f, inner)?;
+                }
+                if parens {
+                    write!(f, ")")?;
+                }
+                if optional {
+                    write!(f, "?")?;
+                }
+                Ok(())
+            },
+            Regex::Star(inner) => {
+                inner.write(f, 2)?;
+                write!(f, "*")
+            }
+        }
+    }
+
+}
+
+// *********************************************************************
+/// Write a symbol so project1 reads it back as itself, escaping every
+/// symbol that is not a letter or digit
+fn write_symbol(f: &mut fmt::Formatter<'_>, c: char) -> fmt::Result {
+    match c {
+        '\n' => write!(f, "\\n"),
+        '\t' => write!(f, "\\t"),
+        '\r' => write!(f, "\\r"),
+        c if c.is_control() || c.is_whitespace() => write!(f, "\\u{{{:X}}}", c as u32),
+        c if c.is_alphanumeric() => write!(f, "{}", c),
+        c => write!(f, "\\{}", c)
+    }
+}
+
+// *********************************************************************
+/// Display the regex in project1's syntax
+impl fmt::Display for Regex {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+
+}
+
+// *********************************************************************
+/// `a` followed by `b`
+pub fn concat(a: Regex, b: Regex) -> Regex {
+
+    if a == Regex::Empty || b == Regex::Empty {
+        return Regex::Empty;
+    }
+
+    let mut items: Vec<Regex> = Vec::new();
+    for r in [a, b] {
+        match r {
+            Regex::Epsilon => (),
+            Regex::Concat(inner) => items.extend(inner),
+            r => items.push(r)
+        }
+    }
+
+    match items.len() {
+        0 => Regex::Epsilon,
+        1 => items.pop().unwrap(),
+        _ => Regex::Concat(items)
+    }
+}
+
+// *********************************************************************
+/// `a` or `b`
+pub fn alternation(a: Regex, b: Regex) -> Regex {
+
+    let mut items: Vec<Regex> = Vec::new();
+    for r in [a, b] {
+        match r {
+            Regex::Empty => (),
+            Regex::Alternation(inner) => items.extend(inner),
+            r => items.push(r)
+        }
+    }
+    items.sort();
+    items.dedup();
+
+    // ε adds nothing next to another item matching the empty string
+    if items.len() > 1 && items.iter().any(|r| *r != Regex::Epsilon && r.nullable()) {
+        items.retain(|r| *r != Regex::Epsilon);
+    }
+
+    match items.len() {
+        0 => Regex::Empty,
+        1 => items.pop().unwrap(),
+        _ => Regex::Alternation(items)
+    }
+}
+
+// *********************************************************************
+/// `a` repeated zero or more times
+pub fn star(a: Regex) -> Regex {
+    match a {
+        Regex::Empty | Regex::Epsilon => Regex::Epsilon,
+        Regex::Star(_) => a,
+        Regex::Alternation(items) if items.contains(&Regex::Epsilon) => {
+            let rest = items.into_iter()
+                            .filter(|r| *r != Regex::Epsilon)
+                            .fold(Regex::Empty, alternation);
+            star(rest)
+        },
+        a => Regex::Star(Box::new(a))
+    }
+}
+
+// *********************************************************************
+/// Which state to remove next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+
+    /// The lowest numbered state left
+    Numbered,
+
+    /// The state with the fewest paths through it, in edges times out edges
+    Degree,
+
+    /// The state whose removal adds the fewest symbols to the edges, the
+    /// weight heuristic of Delgado and Morais
+    Weight,
+}
+
+// *********************************************************************
+/// Implement the methods of the Order structure
+impl Order {
+
+    /// The order named on the command line
+    pub fn from_name(name: &str) -> Option<Order> {
+        match name {
+            "numbered" => Some(Order::Numbered),
+            "degree" => Some(Order::Degree),
+            "weight" => Some(Order::Weight),
+            _ => None
+        }
+    }
+
+}
+
+// *********************************************************************
+/// The regex matching the strings a validated DFA accepts
+///
+/// States that cannot be reached from the start, or cannot reach an accept
+/// state, are dropped first since no accepted string passes through them.
+pub fn to_regex(dfa: &dfa::DFA, order: Order) -> Regex {
+
+    let n = dfa.transitions.len();
+    let useful = useful_states(dfa);
+
+    // States 0..n are those of the DFA (0 relative), n the new start state
+    // and n + 1 the new accept state
+    let mut edges: Vec<Vec<Regex>> = vec![vec![Regex::Empty; n + 2]; n + 2];
+
+    for (rnum, row) in dfa.transitions.iter().enumerate() {
+        for (symbol, state) in dfa.alphabet.iter().zip(row.iter()) {
+            if useful[rnum] && useful[*state - 1] {
+                let edge = std::mem::replace(&mut edges[rnum][*state - 1], Regex::Empty);
+                edges[rnum][*state - 1] = alternation(edge, Regex::Symbol(*symbol));
+            }
+        }
+    }
+    if useful[dfa.start - 1] {
+        edges[n][dfa.start - 1] = Regex::Epsilon;
+    }
+    for state in dfa.accept.iter() {
+        if useful[*state - 1] {
+            edges[*state - 1][n + 1] = Regex::Epsilon;
+        }
+    }
+
+    let mut remaining: Vec<usize> = (0..n).filter(|s| useful[*s]).collect();
+
+    while !remaining.is_empty() {
+
+        let q = remaining.remove(choose(&edges, &remaining, order));
+
+        let repeat = star(std::mem::replace(&mut edges[q][q], Regex::Empty));
+        let ins: Vec<usize> = (0..n + 2).filter(|i| edges[*i][q] != Regex::Empty).collect();
+        let outs: Vec<usize> = (0..n + 2).filter(|j| edges[q][*j] != Regex::Empty).collect();
+
+        for i in ins.iter() {
+            for j in outs.iter() {
+                let path = concat(concat(edges[*i][q].clone(), repeat.clone()), edges[q][*j].clone());
+                let edge = std::mem::replace(&mut edges[*i][*j], Regex::Empty);
+                edges[*i][*j] = alternation(edge, path);
+            }
+        }
+
+        for i in ins {
+            edges[i][q] = Regex::Empty;
+        }
+        for j in outs {
+            edges[q][j] = Regex::Empty;
+        }
+    }
+
+    std::mem::replace(&mut edges[n][n + 1], Regex::Empty)
+}
+
+// *********************************************************************
+/// The states (0 relative) reachable from the start that can also reach
+/// an accept state
+fn useful_states(dfa: &dfa::DFA) -> Vec<bool> {
+
+    let n = dfa.transitions.len();
+
+    let mut reached = vec![false; n];
+    let mut stack = vec![dfa.start - 1];
+    while let Some(s) = stack.pop() {
+        if !reached[s] {
+            reached[s] = true;
+            stack.extend(dfa.transitions[s].iter().map(|t| t - 1));
+        }
+    }
+
+    // Walk back from the accept states until nothing changes
+    let mut accepting = vec![false; n];
+    for state in dfa.accept.iter() {
+        accepting[*state - 1] = true;
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (s, row) in dfa.transitions.iter().enumerate() {
+            if !accepting[s] && row.iter().any(|t| accepting[t - 1]) {
+                accepting[s] = true;
+                changed = true;
+            }
+        }
+    }
+
+    (0..n).map(|s| reached[s] && accepting[s]).collect()
+}
+
+// *********************************************************************
+/// Position in `remaining` of the state to remove next, ties going to the
+/// lowest numbered state
+fn choose(edges: &[Vec<Regex>], remaining: &[usize], order: Order) -> usize {
+
+    let cost = |q: usize| -> usize {
+
+        let ins: Vec<&Regex> = (0..edges.len()).filter(|i| *i != q && edges[*i][q] != Regex::Empty)
+                                               .map(|i| &edges[i][q])
+                                               .collect();
+        let outs: Vec<&Regex> = (0..edges.len()).filter(|j| *j != q && edges[q][*j] != Regex::Empty)
+                                                .map(|j| &edges[q][j])
+                                                .collect();
+
+        match order {
+            Order::Numbered => 0,
+            Order::Degree => ins.len() * outs.len(),
+            Order::Weight => {
+                let paths = ins.len() * outs.len();
+                ins.iter().map(|r| r.size()).sum::<usize>() * outs.len().saturating_sub(1)
+                    + outs.iter().map(|r| r.size()).sum::<usize>() * ins.len().saturating_sub(1)
+                    + edges[q][q].size() * paths.saturating_sub(1)
+            }
+        }
+    };
+
+    (0..remaining.len()).min_by_key(|p| cost(remaining[*p])).unwrap()
+}
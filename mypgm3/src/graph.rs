@@ -50,8 +50,8 @@ impl<'a> State<'a> {
 
     /// Creata a new state, initially with no transitions
     pub fn new(name: String, accept: bool) -> State<'a> {
-        let state = _State { name: name, 
-                             accept: accept, 
+        let state = _State { name, 
+                             accept, 
                              adjacent: HashMap::new() };
         State(Rc::new(RefCell::new(state)))
     }
@@ -120,7 +120,7 @@ impl<'a> Graph<'a> {
 
         Graph{alphabet: dfa.alphabet.clone(),
               start_state: states[dfa.start-1].0.clone(), 
-              states: states}
+              states}
 
     }
 
@@ -157,7 +157,7 @@ impl<'a> fmt::Display for Graph<'a> {
                             a.1.borrow().name,
                             a.0).unwrap()));
 
-        write!(f, "}}\n")
+        writeln!(f, "}}")
     }
     
 }
@@ -6,6 +6,11 @@
 //! Newly added functions:
 //! Test cases
 //! dfa.process()
+//! codegen - write the DFA as a standalone Rust module
+//! regex - convert the DFA into a regex by state elimination
+//! minimize - reduce the DFA to the fewest states with Hopcroft's algorithm
+//! nerode - the table-filling report of which states accept the same strings
+//! product - combine two DFAs by intersection, union, difference or symmetric difference
 //! 
 //! 
 //! Process a yaml format deterministic finite automaton producing
@@ -23,30 +28,104 @@
 //! ```
 //! 
 //! where: `filename` is a yaml file containing the DFA definition
+//!
+//! ```
+//! cargo run -- --minimize filename string
+//! ```
+//!
+//! minimizes the DFA first, writing the state each old state became to
+//! `stderr`.
+//!
+//! ```
+//! cargo run codegen filename rustfile
+//! ```
+//!
+//! writes the DFA as Rust source to `rustfile` instead, and
+//!
+//! ```
+//! cargo run regex filename [numbered|degree|weight]
+//! ```
+//!
+//! writes a regex matching the strings the DFA accepts, and
+//!
+//! ```
+//! cargo run nerode filename [markdown|json]
+//! ```
+//!
+//! writes the table-filling report of which states accept the same strings, and
+//!
+//! ```
+//! cargo run product operation filename1 filename2 yamlfile
+//! ```
+//!
+//! writes the product of two DFAs to `yamlfile` and its Graphviz to `stdout`.
 //! 
 //! # Output
 //! 
 //! To `stderr`: Debug display of the internal graph structure
 //! 
 //! To `stdout`: Graphviz definitions of the graph structure
-use std::io::Write;
+#![allow(clippy::upper_case_acronyms)]
 
+mod bytedfa;
+mod codegen;
 mod dfa;
+mod elimination;
 mod graph;
+mod minimize;
+mod nerode;
+mod product;
 
 // *********************************************************************
 fn main() {
 
+    // `codegen dfafile rustfile` writes the DFA as Rust source instead
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() == 4 && args[1] == "codegen" {
+        write_rust(&args[2], &args[3]);
+        return;
+    }
+
+    // `regex dfafile [order]` writes a regex matching what the DFA accepts
+    if (args.len() == 3 || args.len() == 4) && args[1] == "regex" {
+        write_regex(&args[2], args.get(3).map_or("weight", |o| o.as_str()));
+        return;
+    }
+
+    // `nerode dfafile [format]` writes the table of distinguishable states
+    if (args.len() == 3 || args.len() == 4) && args[1] == "nerode" {
+        write_nerode(&args[2], args.get(3).map_or("markdown", |f| f.as_str()));
+        return;
+    }
+
+    // `product operation dfafile1 dfafile2 yamlfile` combines two DFAs
+    if args.len() == 6 && args[1] == "product" {
+        write_product(&args[2], &args[3], &args[4], &args[5]);
+        return;
+    }
+
     // Get and validate the filename and input string on the command line
-    let (filename, inputstring) = get_arguments(std::env::args());
+    let (filename, inputstring, minimal) = get_arguments(std::env::args());
 
     // Load the yaml file getting a Box pointing to a DFA
     // instance on the heap
-    let dfa = dfa::DFA::new_from_file(&filename);
+    let mut dfa = dfa::DFA::new_from_file(&filename);
 
     // Validate the DFA
     dfa.validate().expect("Validation Failure:");
 
+    // Replace the DFA by its minimal one, writing where each state went to stderr
+    if minimal {
+        let (smaller, mapping) = minimize::minimize(&dfa);
+        for (old, new) in mapping.iter().enumerate() {
+            match new {
+                Some(n) => eprintln!("q{} → q{}", old + 1, n),
+                None => eprintln!("q{} is unreachable", old + 1)
+            }
+        }
+        dfa = smaller;
+    }
+
     // Get a state structure for the DFA
     let graph = graph::Graph::new_from_dfa(&dfa);
 
@@ -57,24 +136,113 @@ fn main() {
     println!{"{}", graph};
 
     //Process the string, print out the processing steps, and print out whether it is accepted or rejected.
-    dfa.process(&inputstring).expect("Processing Failure:");
+    dfa.process(inputstring.as_bytes()).expect("Processing Failure:");
 }
 
 // *********************************************************************
-/// Return the filename and input string passed as the first and second parameter
-fn get_arguments(args: std::env::Args) -> (String, String) {
+/// Load and validate a DFA then write it as a Rust module
+fn write_rust(filename: &str, rustfile: &str) {
+
+    let dfa = dfa::DFA::new_from_file(filename);
+    dfa.validate().expect("Validation Failure:");
+
+    std::fs::write(rustfile, codegen::rust_source(&dfa, filename))
+        .expect("Unable to write output");
+}
 
-    // Get the arguments as a vector
-    let args: Vec<String> = args.collect();
+// *********************************************************************
+/// Load and validate a DFA then write the regex found by state elimination,
+/// removing the states in the order named
+fn write_regex(filename: &str, order: &str) {
+
+    let order = match elimination::Order::from_name(order) {
+        Some(o) => o,
+        None => {
+            eprintln!("Unknown order {}, use numbered, degree or weight", order);
+            std::process::exit(1);
+        }
+    };
+
+    let dfa = dfa::DFA::new_from_file(filename);
+    dfa.validate().expect("Validation Failure:");
+
+    let regex = elimination::to_regex(&dfa, order);
+    eprintln!("{} symbols", regex.size());
+    println!("{}", regex);
+}
+
+// *********************************************************************
+/// Load and validate a DFA then write the table-filling report in the
+/// format named
+fn write_nerode(filename: &str, format: &str) {
+
+    if format != "markdown" && format != "json" {
+        eprintln!("Unknown format {}, use markdown or json", format);
+        std::process::exit(1);
+    }
+
+    let dfa = dfa::DFA::new_from_file(filename);
+    dfa.validate().expect("Validation Failure:");
+
+    let report = nerode::Report::new_from_dfa(&dfa);
+    eprintln!("{} states in {} classes", report.states, report.classes.len());
+    if format == "json" {
+        println!("{}", report.json());
+    }
+    else {
+        print!("{}", report.markdown());
+    }
+}
+
+// *********************************************************************
+/// Load and validate two DFAs then write their product for the operation
+/// named as YAML to a file and as Graphviz to stdout
+fn write_product(operation: &str, first: &str, second: &str, yamlfile: &str) {
+
+    let operation = match product::Operation::from_name(operation) {
+        Some(o) => o,
+        None => {
+            eprintln!("Unknown operation {}, use intersection, union, difference or symmetric-difference", operation);
+            std::process::exit(1);
+        }
+    };
+
+    let a = dfa::DFA::new_from_file(first);
+    a.validate().expect("Validation Failure:");
+    let b = dfa::DFA::new_from_file(second);
+    b.validate().expect("Validation Failure:");
+
+    let dfa = product::product(&a, &b, operation);
+    eprintln!("{} states", dfa.transitions.len());
+
+    std::fs::write(yamlfile, serde_yaml::to_string(&dfa).expect("Unable to write yaml"))
+        .expect("Unable to write output");
+    println!("{}", graph::Graph::new_from_dfa(&dfa));
+}
+
+// *********************************************************************
+/// Return the filename and input string passed as the first and second parameter,
+/// and whether --minimize was given before them
+fn get_arguments(args: std::env::Args) -> (String, String, bool) {
+
+    // Get the arguments as a vector, without --minimize
+    let mut args: Vec<String> = args.collect();
+    let minimal = args.len() > 1 && args[1] == "--minimize";
+    if minimal {
+        args.remove(1);
+    }
 
     // Make sure only one argument was passed
     if args.len() != 3 {
-        writeln!(std::io::stderr(), "Usage: hw1 dfafile")
-            .unwrap();
+        eprintln!("Usage: hw1 [--minimize] dfafile string");
+        eprintln!("       hw1 codegen dfafile rustfile");
+        eprintln!("       hw1 regex dfafile [numbered|degree|weight]");
+        eprintln!("       hw1 nerode dfafile [markdown|json]");
+        eprintln!("       hw1 product intersection|union|difference|symmetric-difference dfafile dfafile yamlfile");
         std::process::exit(1);
     }
     
-    (args[1].to_string(), args[2].to_string())
+    (args[1].to_string(), args[2].to_string(), minimal)
     
 }
 
@@ -82,6 +250,7 @@ fn get_arguments(args: std::env::Args) -> (String, String) {
 mod test {
 
     use super::*;
+    use std::collections::BTreeSet;
 
     //This test is used to make sure that it fails when it can't find the filename given. 
     #[test]
@@ -95,7 +264,7 @@ mod test {
     fn test2() {
         let dfa = dfa::DFA::new_from_file("sample.yaml");
         
-        dfa.process("xyxxyx").expect("Processing Failure:");
+        dfa.process(b"xyxxyx").expect("Processing Failure:");
     }
 
     //This test is used to make sure that it rejects a language with incorrect symbols
@@ -104,6 +273,425 @@ mod test {
     fn test3() {
         let dfa = dfa::DFA::new_from_file("sample.yaml");
         
-        dfa.process("xxy101yx").expect("Processing Failure:");
+        dfa.process(b"xxy101yx").expect("Processing Failure:");
+    }
+
+    //A xorshift generator of numbers below the bound it is given, the same for a seed on every run
+    fn xorshift(mut seed: u32) -> impl FnMut(usize) -> usize {
+        move |bound| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as usize % bound
+        }
+    }
+
+    //Compile the generated source with a main printing whether each line of stdin is accepted
+    fn compile_generated(code: &str, name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("mypgm3-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let source = dir.join("main.rs");
+        let program = format!("{}\nfn main() {{\n    use std::io::BufRead;\n    \
+                               for line in std::io::stdin().lock().lines() {{\n        \
+                               println!(\"{{}}\", accepts(&line.unwrap()));\n    }}\n}}\n", code);
+        std::fs::write(&source, program).unwrap();
+
+        let exe = dir.join("generated");
+        let status = std::process::Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+                        .arg("--edition=2018")
+                        .arg("-o").arg(&exe)
+                        .arg(&source)
+                        .status()
+                        .expect("Unable to run rustc");
+        assert!(status.success(), "generated code for {} does not compile", name);
+        exe
+    }
+
+    //This test is used to make sure that the generated Rust code accepts the same strings as the DFA
+    #[test]
+    fn test4() {
+        let sample = dfa::DFA::new_from_file("sample.yaml");
+        let quoted = dfa::DFA{alphabet: vec!['\'', '\\', 'é'],
+                              start: 1,
+                              accept: vec![],
+                              transitions: vec![vec![2, 1, 1], vec![2, 2, 1]]};
+
+        for (name, dfa) in [("sample", &*sample), ("quoted", &quoted)].iter() {
+            dfa.validate().unwrap();
+            let exe = compile_generated(&codegen::rust_source(dfa, name), name);
+
+            //Random strings over the alphabet and one symbol outside it, from a xorshift generator
+            let mut symbols = dfa.alphabet.clone();
+            symbols.push('z');
+            let mut next = xorshift(2463534242);
+            let inputs: Vec<String> = (0..500).map(|_| {
+                let length = next(12);
+                (0..length).map(|_| symbols[next(symbols.len())]).collect()
+            }).collect();
+
+            let mut child = std::process::Command::new(&exe)
+                                .stdin(std::process::Stdio::piped())
+                                .stdout(std::process::Stdio::piped())
+                                .spawn()
+                                .unwrap();
+            {
+                use std::io::Write;
+                let stdin = child.stdin.as_mut().unwrap();
+                for input in inputs.iter() {
+                    writeln!(stdin, "{}", input).unwrap();
+                }
+            }
+            let output = String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap();
+
+            //The interpreter rejects a symbol outside the alphabet with an error
+            let bytes = bytedfa::ByteDFA::new_from_dfa(dfa);
+            let expected: Vec<String> = inputs.iter().map(|i| bytes.process(i.as_bytes()).unwrap_or(false).to_string()).collect();
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+
+            std::fs::remove_dir_all(exe.parent().unwrap()).unwrap();
+        }
+    }
+
+    //This test is used to make sure that bytes are read as UTF-8, rejecting symbols outside the alphabet and invalid UTF-8 with an error
+    #[test]
+    fn test5() {
+        let dfa = dfa::DFA{alphabet: vec!['a', 'é', '€', '😀'],
+                           start: 1,
+                           accept: vec![2],
+                           transitions: vec![vec![1, 2, 1, 2], vec![2, 1, 2, 1]]};
+        dfa.validate().unwrap();
+        let bytes = bytedfa::ByteDFA::new_from_dfa(&dfa);
+
+        assert_eq!(bytes.process("aé€a".as_bytes()), Ok(true));
+        assert_eq!(bytes.process("é😀".as_bytes()), Ok(false));
+        assert_eq!(bytes.process(b""), Ok(false));
+
+        //é and è share their first byte
+        assert_eq!(bytes.process("aè".as_bytes()), Err("è is not a valid symbol in the language. Rejected.".to_string()));
+        assert_eq!(bytes.process(b"a\xff"), Err("Invalid UTF-8 at byte 1. Rejected.".to_string()));
+        assert_eq!(bytes.process(b"a\xe2\x82"), Err("Invalid UTF-8 at byte 1. Rejected.".to_string()));
+        assert_eq!(bytes.process(b"\xc3\xa9\xc3a"), Err("Invalid UTF-8 at byte 2. Rejected.".to_string()));
+    }
+
+    //A random DFA with the number of states given over the alphabet, about a third of its states accepting
+    fn random_dfa(next: &mut impl FnMut(usize) -> usize, states: usize, alphabet: &[char]) -> dfa::DFA {
+        let transitions = (0..states).map(|_| alphabet.iter().map(|_| 1 + next(states)).collect()).collect();
+        let accept = (1..=states).filter(|_| next(3) == 0).collect();
+        dfa::DFA{alphabet: alphabet.to_vec(), start: 1 + next(states), accept, transitions}
+    }
+
+    //Every string over the alphabet up to the length, shortest first
+    fn strings_up_to(alphabet: &[char], length: usize) -> Vec<String> {
+        let mut strings: Vec<String> = vec![String::new()];
+        let mut last: Vec<String> = strings.clone();
+        for _ in 0..length {
+            last = last.iter().flat_map(|s| alphabet.iter().map(move |c| format!("{}{}", s, c))).collect();
+            strings.extend(last.iter().cloned());
+        }
+        strings
+    }
+
+    //The positions of the input where a match of the regex ending there started at one of the starts
+    fn regex_ends(regex: &elimination::Regex, input: &[char], starts: BTreeSet<usize>) -> BTreeSet<usize> {
+        match regex {
+            elimination::Regex::Empty => BTreeSet::new(),
+            elimination::Regex::Epsilon => starts,
+            elimination::Regex::Symbol(c) => starts.iter().filter(|i| input.get(**i) == Some(c)).map(|i| i + 1).collect(),
+            elimination::Regex::Concat(items) => items.iter().fold(starts, |ends, item| regex_ends(item, input, ends)),
+            elimination::Regex::Alternation(items) => items.iter().flat_map(|item| regex_ends(item, input, starts.clone())).collect(),
+            elimination::Regex::Star(item) => {
+                //Add the ends of one more repetition until none are new
+                let mut ends = starts.clone();
+                let mut new = starts;
+                while !new.is_empty() {
+                    new = regex_ends(item, input, new).difference(&ends).cloned().collect();
+                    ends.extend(new.iter().cloned());
+                }
+                ends
+            }
+        }
     }
-}
\ No newline at end of file
+
+    //Does the regex match the whole input
+    fn regex_accepts(regex: &elimination::Regex, input: &str) -> bool {
+        let input: Vec<char> = input.chars().collect();
+        regex_ends(regex, &input, [0].iter().cloned().collect()).contains(&input.len())
+    }
+
+    //The sample, one with nothing accepted and random ones over symbols that need escaping
+    fn elimination_dfas() -> Vec<dfa::DFA> {
+        let mut next = xorshift(88675123);
+
+        let mut dfas = vec![*dfa::DFA::new_from_file("sample.yaml"),
+                            dfa::DFA{alphabet: vec!['a'], start: 1, accept: vec![], transitions: vec![vec![1]]}];
+        for states in 1..6 {
+            let alphabet = ['a', '*', '|', ' ', '-', 'é'];
+            let length = 2 + next(4);
+            dfas.push(random_dfa(&mut next, states, &alphabet[..length]));
+        }
+        dfas
+    }
+
+    //This test is used to make sure that the regex from state elimination accepts the same strings as the DFA
+    #[test]
+    fn test6() {
+        for dfa in elimination_dfas().iter() {
+            dfa.validate().unwrap();
+
+            //Every string up to length 4 over the alphabet
+            let inputs = strings_up_to(&dfa.alphabet, 4);
+            let bytes = bytedfa::ByteDFA::new_from_dfa(dfa);
+            let expected: Vec<bool> = inputs.iter().map(|i| bytes.process(i.as_bytes()).unwrap()).collect();
+
+            let sizes: Vec<usize> = [elimination::Order::Numbered, elimination::Order::Degree, elimination::Order::Weight].iter().map(|order| {
+                let regex = elimination::to_regex(dfa, *order);
+                assert_eq!(inputs.iter().map(|i| regex_accepts(&regex, i)).collect::<Vec<bool>>(), expected, "{}", regex);
+                regex.size()
+            }).collect();
+
+            //The weight heuristic never does worse than taking the states in order here
+            assert!(sizes[2] <= sizes[0], "{:?}", sizes);
+        }
+    }
+
+    //This test is used to make sure that Hopcroft's algorithm keeps the strings accepted and leaves no two states that accept the same strings
+    #[test]
+    fn test7() {
+        let mut next = xorshift(362436069);
+
+        //Two copies of the sample's states, and a state nothing reaches
+        let doubled = dfa::DFA{alphabet: vec!['x', 'y'],
+                               start: 3,
+                               accept: vec![2, 3, 5, 6],
+                               transitions: vec![vec![4, 2], vec![5, 3], vec![1, 6], vec![1, 5], vec![2, 6], vec![4, 3], vec![7, 1]]};
+        let (minimal, mapping) = minimize::minimize(&doubled);
+        assert_eq!(minimal.transitions, vec![vec![2, 1], vec![2, 3], vec![3, 1]]);
+        assert_eq!(minimal.accept, vec![1, 3]);
+        assert_eq!(mapping, vec![Some(2), Some(3), Some(1), Some(2), Some(3), Some(1), None]);
+
+        let mut dfas = vec![Box::new(doubled), dfa::DFA::new_from_file("sample.yaml")];
+        for states in 1..40 {
+            let length = 1 + next(3);
+            dfas.push(Box::new(random_dfa(&mut next, states, &['a', 'b', 'c'][..length])));
+        }
+
+        for dfa in dfas.iter() {
+            dfa.validate().unwrap();
+            let (minimal, mapping) = minimize::minimize(dfa);
+            minimal.validate().unwrap();
+            assert_eq!(minimal.start, mapping[dfa.start - 1].unwrap());
+
+            //Each reachable state goes where its old transitions went and accepts the same way
+            for (old, new) in mapping.iter().enumerate() {
+                if let Some(new) = new {
+                    assert_eq!(minimal.accept.contains(new), dfa.accept.contains(&(old + 1)));
+                    for (c, t) in dfa.transitions[old].iter().enumerate() {
+                        assert_eq!(Some(minimal.transitions[new - 1][c]), mapping[t - 1]);
+                    }
+                }
+            }
+
+            //No two states accept the same strings, found by refining the
+            //blocks of accept states by the blocks each symbol leads to
+            let mut blocks: Vec<Vec<usize>> = (1..=minimal.transitions.len()).map(|s| vec![usize::from(minimal.accept.contains(&s))]).collect();
+            for _ in 0..minimal.transitions.len() {
+                blocks = (0..minimal.transitions.len()).map(|s| {
+                    let mut key = blocks[s].clone();
+                    key.extend(minimal.transitions[s].iter().flat_map(|t| blocks[t - 1].clone()));
+                    key
+                }).collect();
+                let mut distinct = blocks.clone();
+                distinct.sort();
+                distinct.dedup();
+                blocks = blocks.iter().map(|b| vec![distinct.binary_search(b).unwrap()]).collect();
+            }
+            let mut distinct = blocks.clone();
+            distinct.sort();
+            distinct.dedup();
+            assert_eq!(distinct.len(), minimal.transitions.len(), "{:?}", dfa);
+        }
+    }
+
+    //This test is used to make sure that the table-filling report finds shortest separating suffixes and the classes Hopcroft's algorithm merges
+    #[test]
+    fn test8() {
+        let mut next = xorshift(521288629);
+
+        //Two copies of the sample's states, and a state nothing reaches
+        let doubled = dfa::DFA{alphabet: vec!['x', 'y', '|'],
+                               start: 3,
+                               accept: vec![2, 3, 5, 6],
+                               transitions: vec![vec![4, 2, 1], vec![5, 3, 2], vec![1, 6, 3], vec![1, 5, 4],
+                                                 vec![2, 6, 5], vec![4, 3, 6], vec![7, 1, 7]]};
+        let report = nerode::Report::new_from_dfa(&doubled);
+        assert_eq!(report.classes, vec![vec![1, 4], vec![2, 5], vec![3, 6], vec![7]]);
+        assert!(report.markdown().contains("| q7 | `y` | ε | ε | `y` | ε | ε |\n"), "{}", report.markdown());
+
+        let json: serde_json::Value = serde_json::from_str(&report.json()).unwrap();
+        assert_eq!(json["pairs"][0], serde_json::json!({"p": 1, "q": 2, "suffix": ""}));
+        assert_eq!(json["pairs"][2], serde_json::json!({"p": 1, "q": 4, "suffix": null}));
+        assert_eq!(json["classes"][0], serde_json::json!([1, 4]));
+
+        let mut dfas = vec![Box::new(doubled), dfa::DFA::new_from_file("sample.yaml")];
+        for states in 1..12 {
+            let length = 1 + next(3);
+            dfas.push(Box::new(random_dfa(&mut next, states, &['a', 'b', 'c'][..length])));
+        }
+
+        for dfa in dfas.iter() {
+            dfa.validate().unwrap();
+            let report = nerode::Report::new_from_dfa(dfa);
+            let accepts = |state: usize, input: &str| {
+                let end = input.chars().fold(state, |s, c| dfa.transitions[s - 1][dfa.alphabet.iter().position(|a| *a == c).unwrap()]);
+                dfa.accept.contains(&end)
+            };
+
+            //Every string up to length 4 over the alphabet, shortest first
+            let strings = strings_up_to(&dfa.alphabet, 4);
+
+            //The suffix tells the states apart and no shorter string does
+            for pair in report.pairs.iter() {
+                let first = strings.iter().find(|s| accepts(pair.p, s) != accepts(pair.q, s));
+                match &pair.suffix {
+                    Some(suffix) => {
+                        assert_ne!(accepts(pair.p, suffix), accepts(pair.q, suffix));
+                        if let Some(first) = first {
+                            assert_eq!(suffix.chars().count(), first.chars().count());
+                        }
+                    },
+                    None => assert_eq!(first, None)
+                }
+            }
+
+            //The classes of the states that can be reached are the states Hopcroft's algorithm leaves
+            let (minimal, mapping) = minimize::minimize(dfa);
+            let reached: Vec<Vec<usize>> = report.classes.iter()
+                                                  .map(|c| c.iter().filter_map(|s| mapping[s - 1]).collect::<Vec<usize>>())
+                                                  .filter(|c| !c.is_empty())
+                                                  .collect();
+            assert_eq!(reached.len(), minimal.transitions.len());
+            for class in reached {
+                assert!(class.iter().all(|s| *s == class[0]), "{:?}", class);
+            }
+        }
+    }
+
+    //This test is used to make sure that the product of two DFAs over different alphabets accepts what the operation keeps and reads back from its YAML
+    #[test]
+    fn test9() {
+        let mut next = xorshift(1597334677);
+
+        //The dead state is only added for symbols the DFA lacks
+        let sample = dfa::DFA::new_from_file("sample.yaml");
+        assert_eq!(product::unify(&sample, &['y', 'x']).transitions, vec![vec![2, 1], vec![3, 2], vec![3, 1]]);
+        assert_eq!(product::unify(&sample, &['x', 'z', 'y']).transitions, vec![vec![1, 4, 2], vec![2, 4, 3], vec![1, 4, 3], vec![4, 4, 4]]);
+
+        let operations = [product::Operation::Intersection, product::Operation::Union,
+                          product::Operation::Difference, product::Operation::SymmetricDifference];
+
+        for _ in 0..20 {
+            let mut dfas: Vec<dfa::DFA> = Vec::new();
+            for alphabet in [vec!['a', 'b', 'é'], vec!['b', 'c', 'é']].iter() {
+                let alphabet: Vec<char> = alphabet.iter().filter(|_| next(4) != 0).cloned().collect();
+                let states = 1 + next(5);
+                dfas.push(random_dfa(&mut next, states, &alphabet));
+            }
+            let (a, b) = (&dfas[0], &dfas[1]);
+            a.validate().unwrap();
+            b.validate().unwrap();
+
+            //A string with a symbol outside the alphabet is rejected
+            let accepts = |dfa: &dfa::DFA, input: &str| {
+                let mut state = dfa.start;
+                for c in input.chars() {
+                    match dfa.alphabet.iter().position(|a| *a == c) {
+                        Some(column) => state = dfa.transitions[state - 1][column],
+                        None => return false
+                    }
+                }
+                dfa.accept.contains(&state)
+            };
+
+            for operation in operations.iter() {
+                let combined = product::product(a, b, *operation);
+                combined.validate().unwrap();
+                assert!(combined.transitions.len() <= (a.transitions.len() + 1) * (b.transitions.len() + 1));
+
+                //Every string up to length 4 over both alphabets
+                for s in strings_up_to(&combined.alphabet, 4).iter() {
+                    assert_eq!(accepts(&combined, s), operation.accepts(accepts(a, s), accepts(b, s)), "{:?} {}", operation, s);
+                }
+
+                //The YAML reads back as the same DFA
+                let read: dfa::DFA = serde_yaml::from_str(&serde_yaml::to_string(&combined).unwrap()).unwrap();
+                assert_eq!((read.alphabet, read.start, read.accept, read.transitions),
+                           (combined.alphabet.clone(), combined.start, combined.accept.clone(), combined.transitions.clone()));
+            }
+        }
+    }
+
+    //Build project1 with cargo and return its binary, None when it cannot be built
+    fn project1_binary() -> Option<std::path::PathBuf> {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let built = std::process::Command::new(&cargo)
+                        .args(["build", "--quiet", "--manifest-path", "../project1/Cargo.toml"])
+                        .status()
+                        .is_ok_and(|status| status.success());
+
+        //project1 builds into CARGO_TARGET_DIR when it is set, otherwise its own target directory
+        let target = std::env::var_os("CARGO_TARGET_DIR").map_or_else(|| std::path::PathBuf::from("../project1/target"), std::path::PathBuf::from);
+        let exe = target.join("debug").join(format!("project1{}", std::env::consts::EXE_SUFFIX));
+        if built && exe.is_file() { Some(exe) } else { None }
+    }
+
+    //Run project1 on a regex, returning whether it accepts each input
+    fn project1_accepts(exe: &std::path::Path, regex: &str, inputs: &[String]) -> Vec<bool> {
+        let mut child = std::process::Command::new(exe)
+                            .arg(regex)
+                            .stdin(std::process::Stdio::piped())
+                            .stdout(std::process::Stdio::piped())
+                            .stderr(std::process::Stdio::piped())
+                            .spawn()
+                            .unwrap();
+        {
+            use std::io::Write;
+            let stdin = child.stdin.as_mut().unwrap();
+            for input in inputs.iter() {
+                writeln!(stdin, "{}", input).unwrap();
+            }
+        }
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "project1 rejects {}: {}", regex, String::from_utf8_lossy(&output.stderr));
+
+        String::from_utf8(output.stdout).unwrap()
+            .lines()
+            .filter(|l| *l == "Accept" || *l == "Reject")
+            .map(|l| l == "Accept")
+            .collect()
+    }
+
+    //This test is used to make sure that project1 reads the regexes from state elimination and accepts the same strings as the DFA
+    #[test]
+    fn test10() {
+        let exe = match project1_binary() {
+            Some(exe) => exe,
+            None => {
+                eprintln!("test10 skipped, project1 could not be built");
+                return;
+            }
+        };
+
+        for dfa in elimination_dfas().iter() {
+            //Every string up to length 4 over the alphabet
+            let inputs = strings_up_to(&dfa.alphabet, 4);
+            let bytes = bytedfa::ByteDFA::new_from_dfa(dfa);
+            let expected: Vec<bool> = inputs.iter().map(|i| bytes.process(i.as_bytes()).unwrap()).collect();
+
+            for order in [elimination::Order::Numbered, elimination::Order::Degree, elimination::Order::Weight].iter() {
+                let regex = elimination::to_regex(dfa, *order);
+                assert_eq!(project1_accepts(&exe, &regex.to_string(), &inputs), expected, "{}", regex);
+            }
+        }
+    }
+}
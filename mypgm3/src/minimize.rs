@@ -0,0 +1,218 @@
+//! CSIS-616 - Program #3
+//!
+//! Reduce a DFA to the fewest states with Hopcroft's algorithm.
+//!
+//! States that cannot be reached from the start are dropped, and the rest
+//! start out split into accept and non-accept blocks. A block and a symbol
+//! taken from the list of splitters split every block holding both states
+//! that go into the splitter on that symbol and states that do not. When a
+//! block splits, only the smaller half has to be added to the list unless
+//! the block was still waiting there, which keeps the work to
+//! O(n log n) for n states times the size of the alphabet. Once the list is
+//! empty no block can be split and each block becomes one state.
+
+use crate::dfa;
+
+// *********************************************************************
+/// # Partition Structure
+///
+/// The states are kept in one array with each block a range of it, so a
+/// block is split by moving the states marked in it to the front of its
+/// range.
+struct Partition {
+
+    /// The states, each block's together
+    states: Vec<usize>,
+
+    /// Where each state is in `states`
+    location: Vec<usize>,
+
+    /// The block each state is in
+    block: Vec<usize>,
+
+    /// The range of `states` holding each block
+    ranges: Vec<(usize, usize)>,
+
+    /// How many states at the front of each block are marked
+    marked: Vec<usize>,
+
+    /// Blocks with a marked state
+    touched: Vec<usize>,
+}
+
+// *********************************************************************
+/// Implement the methods of the Partition structure
+impl Partition {
+
+    /// The states 0..n in blocks numbered by `block`
+    fn new(block: Vec<usize>, blocks: usize) -> Partition {
+
+        let mut states: Vec<usize> = (0..block.len()).collect();
+        states.sort_by_key(|s| block[*s]);
+
+        let mut location = vec![0; block.len()];
+        let mut ranges = vec![(0, 0); blocks];
+        for (i, s) in states.iter().enumerate() {
+            location[*s] = i;
+            if i == 0 || block[states[i - 1]] != block[*s] {
+                ranges[block[*s]].0 = i;
+            }
+            ranges[block[*s]].1 = i + 1;
+        }
+
+        Partition{states, location, block, ranges, marked: vec![0; blocks], touched: vec!()}
+    }
+
+    /// The states in a block
+    fn members(&self, b: usize) -> &[usize] {
+        &self.states[self.ranges[b].0..self.ranges[b].1]
+    }
+
+    /// Move a state to the marked front of its block
+    fn mark(&mut self, s: usize) {
+
+        let b = self.block[s];
+        let i = self.location[s];
+        let j = self.ranges[b].0 + self.marked[b];
+        if i < j {
+            return;
+        }
+
+        let other = self.states[j];
+        self.states.swap(i, j);
+        self.location[other] = i;
+        self.location[s] = j;
+        self.marked[b] += 1;
+        if self.marked[b] == 1 {
+            self.touched.push(b);
+        }
+    }
+
+    /// Make a new block of the marked states of every block where some
+    /// states are not marked, returning the old and new block numbers
+    fn split(&mut self) -> Vec<(usize, usize)> {
+
+        let mut splits: Vec<(usize, usize)> = Vec::new();
+
+        for b in std::mem::take(&mut self.touched) {
+            let (first, end) = self.ranges[b];
+            let marked = std::mem::replace(&mut self.marked[b], 0);
+            if marked == end - first {
+                continue;
+            }
+
+            let new = self.ranges.len();
+            self.ranges.push((first, first + marked));
+            self.marked.push(0);
+            self.ranges[b].0 = first + marked;
+            for i in first..first + marked {
+                self.block[self.states[i]] = new;
+            }
+            splits.push((b, new));
+        }
+
+        splits
+    }
+
+    /// Number of states in a block
+    fn len(&self, b: usize) -> usize {
+        self.ranges[b].1 - self.ranges[b].0
+    }
+}
+
+// *********************************************************************
+/// The DFA with the fewest states accepting the same strings as a
+/// validated DFA, and the state (1 relative) each old state became, None
+/// for states that cannot be reached
+///
+/// The new states are numbered in the order they are reached from the
+/// start, so the start state is state 1.
+pub fn minimize(dfa: &dfa::DFA) -> (Box<dfa::DFA>, Vec<Option<usize>>) {
+
+    let symbols = dfa.alphabet.len();
+
+    // States reachable from the start (0 relative), in the order they are
+    // found, and the number each is given for the partition
+    let mut order: Vec<usize> = vec![dfa.start - 1];
+    let mut index: Vec<Option<usize>> = vec![None; dfa.transitions.len()];
+    index[dfa.start - 1] = Some(0);
+    let mut current = 0;
+    while current < order.len() {
+        for t in dfa.transitions[order[current]].iter() {
+            if index[t - 1].is_none() {
+                index[t - 1] = Some(order.len());
+                order.push(t - 1);
+            }
+        }
+        current += 1;
+    }
+    let n = order.len();
+
+    // The states going to each state on each symbol
+    let mut inverse: Vec<Vec<Vec<usize>>> = vec![vec![vec!(); n]; symbols];
+    for (s, old) in order.iter().enumerate() {
+        for (c, t) in dfa.transitions[*old].iter().enumerate() {
+            inverse[c][index[t - 1].unwrap()].push(s);
+        }
+    }
+
+    // Accept states in block 0 and the others in block 1, or all in block 0
+    let accepting: Vec<bool> = order.iter().map(|s| dfa.accept.contains(&(s + 1))).collect();
+    let mixed = accepting.iter().any(|a| *a) && accepting.iter().any(|a| !*a);
+    let block: Vec<usize> = accepting.iter().map(|a| usize::from(mixed && !*a)).collect();
+    let mut partition = Partition::new(block, 1 + usize::from(mixed));
+
+    // The splitters still to be used, and whether each block and symbol is
+    // one of them
+    let mut splitters: Vec<(usize, usize)> = Vec::new();
+    let mut waiting: Vec<Vec<bool>> = vec![vec![false; symbols]; 1 + usize::from(mixed)];
+    if mixed {
+        let smaller = if partition.len(0) <= partition.len(1) { 0 } else { 1 };
+        splitters.extend((0..symbols).map(|c| (smaller, c)));
+        waiting[smaller] = vec![true; symbols];
+    }
+
+    while let Some((splitter, c)) = splitters.pop() {
+        waiting[splitter][c] = false;
+
+        let targets = partition.members(splitter).to_vec();
+        for t in targets {
+            for s in inverse[c][t].iter() {
+                partition.mark(*s);
+            }
+        }
+
+        for (old, new) in partition.split() {
+            waiting.push(vec![false; symbols]);
+            let smaller = if partition.len(new) <= partition.len(old) { new } else { old };
+            let added: Vec<usize> = waiting[old].iter().map(|w| if *w { new } else { smaller }).collect();
+            for (d, b) in added.into_iter().enumerate() {
+                splitters.push((b, d));
+                waiting[b][d] = true;
+            }
+        }
+    }
+
+    // Number the blocks in the order their first state was reached
+    let mut numbers: Vec<Option<usize>> = vec![None; partition.ranges.len()];
+    let mut firsts: Vec<usize> = Vec::new();
+    for (s, old) in order.iter().enumerate() {
+        let b = partition.block[s];
+        if numbers[b].is_none() {
+            numbers[b] = Some(firsts.len() + 1);
+            firsts.push(*old);
+        }
+    }
+
+    let mapping: Vec<Option<usize>> = index.iter().map(|i| i.and_then(|s| numbers[partition.block[s]])).collect();
+
+    // The first state reached in each block stands for all of it
+    let minimal = dfa::DFA{alphabet: dfa.alphabet.clone(),
+                           start: 1,
+                           accept: (1..=firsts.len()).filter(|b| dfa.accept.contains(&(firsts[b - 1] + 1))).collect(),
+                           transitions: firsts.iter()
+                                              .map(|s| dfa.transitions[*s].iter().map(|t| mapping[t - 1].unwrap()).collect())
+                                              .collect()};
+
+    (Box::new(minimal), mapping)
+}
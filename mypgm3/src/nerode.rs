@@ -0,0 +1,184 @@
+//! CSIS-616 - Program #3
+//!
+//! Find which states of a DFA accept the same strings with the
+//! table-filling algorithm, the Myhill–Nerode equivalence of its states.
+//!
+//! Every pair of states has a cell in the table. First the pairs where one
+//! state accepts and the other does not are marked, told apart by the empty
+//! string. Then in each round a pair is marked when some symbol takes it to
+//! a pair marked in an earlier round, told apart by that symbol followed by
+//! the suffix of that pair. Round k marks exactly the pairs whose shortest
+//! separating suffix has k symbols, so the suffixes found are as short as
+//! possible. The pairs never marked are equivalent.
+//!
+//! Every state is in the table, including those that cannot be reached.
+
+use serde::Serialize;
+
+use crate::dfa;
+
+// *********************************************************************
+/// # Pair Structure
+///
+/// One cell of the table
+#[derive(Debug, Serialize)]
+pub struct Pair {
+
+    /// The lower numbered state (1 relative)
+    pub p: usize,
+
+    /// The higher numbered state (1 relative)
+    pub q: usize,
+
+    /// A shortest string accepted from one state and rejected from the
+    /// other, None when the states are equivalent
+    pub suffix: Option<String>,
+}
+
+// *********************************************************************
+/// # Report Structure
+#[derive(Debug, Serialize)]
+pub struct Report {
+
+    /// The set of characters comprising the alphabet
+    pub alphabet: Vec<char>,
+
+    /// Number of states
+    pub states: usize,
+
+    /// Every pair of states, by p then q
+    pub pairs: Vec<Pair>,
+
+    /// The states (1 relative) accepting the same strings, by their lowest
+    pub classes: Vec<Vec<usize>>,
+}
+
+// *********************************************************************
+/// Implement the methods of the Report structure
+impl Report {
+
+    /// Fill the table for a validated DFA
+    pub fn new_from_dfa(dfa: &dfa::DFA) -> Report {
+
+        let n = dfa.transitions.len();
+        let accepting: Vec<bool> = (1..=n).map(|s| dfa.accept.contains(&s)).collect();
+
+        // Kept for both orders of each pair (0 relative)
+        let mut table: Vec<Vec<Option<String>>> = vec![vec![None; n]; n];
+        for p in 0..n {
+            for q in 0..n {
+                if accepting[p] != accepting[q] {
+                    table[p][q] = Some(String::new());
+                }
+            }
+        }
+
+        // Mark from the pairs of the round before until a round marks nothing
+        loop {
+            let mut marked: Vec<(usize, usize, String)> = Vec::new();
+            for p in 0..n {
+                for q in p + 1..n {
+                    if table[p][q].is_some() {
+                        continue;
+                    }
+                    let found = dfa.alphabet.iter().enumerate().find_map(|(c, symbol)| {
+                        let (s, t) = (dfa.transitions[p][c] - 1, dfa.transitions[q][c] - 1);
+                        table[s][t].as_ref().map(|suffix| format!("{}{}", symbol, suffix))
+                    });
+                    if let Some(suffix) = found {
+                        marked.push((p, q, suffix));
+                    }
+                }
+            }
+
+            if marked.is_empty() {
+                break;
+            }
+            for (p, q, suffix) in marked {
+                table[q][p] = Some(suffix.clone());
+                table[p][q] = Some(suffix);
+            }
+        }
+
+        // Each state not yet in a class starts one with the states after it
+        // left unmarked beside it
+        let mut classes: Vec<Vec<usize>> = Vec::new();
+        let mut placed = vec![false; n];
+        for p in 0..n {
+            if placed[p] {
+                continue;
+            }
+            let class: Vec<usize> = (p..n).filter(|q| *q == p || table[p][*q].is_none()).collect();
+            for q in class.iter() {
+                placed[*q] = true;
+            }
+            classes.push(class.iter().map(|q| q + 1).collect());
+        }
+
+        let pairs = (0..n).flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+                          .map(|(p, q)| Pair{p: p + 1, q: q + 1, suffix: table[p][q].clone()})
+                          .collect();
+
+        Report{alphabet: dfa.alphabet.clone(), states: n, pairs, classes}
+    }
+
+    /// The suffix separating two different states (1 relative)
+    fn suffix(&self, p: usize, q: usize) -> &Option<String> {
+        let (p, q) = if p < q { (p, q) } else { (q, p) };
+
+        // The pairs with a lower p come first, n - p' of them for each p'
+        let before: usize = (1..p).map(|r| self.states - r).sum();
+        &self.pairs[before + q - p - 1].suffix
+    }
+
+    /// The report as Markdown: the table below the diagonal, a row for each
+    /// state after the first and a column for each state before the last,
+    /// then the classes
+    pub fn markdown(&self) -> String {
+
+        let mut text = String::from("## Distinguishing suffixes\n\n");
+        text.push_str("ε marks a pair told apart by the empty string, ≡ a pair that is equivalent.\n\n");
+
+        if self.states > 1 {
+            let columns: Vec<String> = (1..self.states).map(|q| format!("q{}", q)).collect();
+            text.push_str(&format!("| | {} |\n", columns.join(" | ")));
+            text.push_str(&format!("|---|{}\n", "---|".repeat(columns.len())));
+
+            for q in 2..=self.states {
+                let cells: Vec<String> = (1..self.states).map(|p| if p < q { cell(self.suffix(p, q)) } else { String::new() })
+                                                         .collect();
+                text.push_str(&format!("| q{} | {} |\n", q, cells.join(" | ")));
+            }
+            text.push('\n');
+        }
+
+        text.push_str("## Equivalence classes\n\n");
+        for class in self.classes.iter() {
+            let names: Vec<String> = class.iter().map(|s| format!("q{}", s)).collect();
+            text.push_str(&format!("- {{{}}}\n", names.join(", ")));
+        }
+
+        text
+    }
+
+    /// The report as JSON, the empty suffix written as "" and an
+    /// equivalent pair's as null
+    pub fn json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Unable to write json")
+    }
+
+}
+
+// *********************************************************************
+/// A cell of the Markdown table, the suffix as code with the bars that
+/// would end the cell escaped
+fn cell(suffix: &Option<String>) -> String {
+    match suffix {
+        None => "≡".to_string(),
+        Some(s) if s.is_empty() => "ε".to_string(),
+        Some(s) => {
+            let s = s.replace('|', "\\|");
+            if s.contains('`') { format!("`` {} ``", s) } else { format!("`{}`", s) }
+        }
+    }
+}
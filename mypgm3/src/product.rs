@@ -0,0 +1,131 @@
+//! CSIS-616 - Program #3
+//!
+//! Combine two DFAs into one with the product construction.
+//!
+//! The two DFAs are first given the same alphabet, their symbols together.
+//! A DFA missing some of the symbols gets a dead state, which rejects and
+//! goes back to itself on every symbol, and every missing symbol goes
+//! there. The product then runs both DFAs side by side: each of its states
+//! is a pair of states, one from each DFA, and a symbol moves both. Only
+//! the pairs reachable from the pair of start states are built. Whether a
+//! pair accepts depends on whether each of its states accepts, by the
+//! operation chosen.
+
+use std::collections::HashMap;
+
+use crate::dfa;
+
+// *********************************************************************
+/// How the accept states of the two DFAs decide a pair's
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+
+    /// Both accept
+    Intersection,
+
+    /// Either accepts
+    Union,
+
+    /// The first accepts and the second does not
+    Difference,
+
+    /// Exactly one accepts
+    SymmetricDifference,
+}
+
+// *********************************************************************
+/// Implement the methods of the Operation structure
+impl Operation {
+
+    /// The operation named on the command line
+    pub fn from_name(name: &str) -> Option<Operation> {
+        match name {
+            "intersection" => Some(Operation::Intersection),
+            "union" => Some(Operation::Union),
+            "difference" => Some(Operation::Difference),
+            "symmetric-difference" => Some(Operation::SymmetricDifference),
+            _ => None
+        }
+    }
+
+    /// Does a pair accept when its states do or do not
+    pub fn accepts(&self, a: bool, b: bool) -> bool {
+        match self {
+            Operation::Intersection => a && b,
+            Operation::Union => a || b,
+            Operation::Difference => a && !b,
+            Operation::SymmetricDifference => a != b
+        }
+    }
+
+}
+
+// *********************************************************************
+/// A validated DFA over `alphabet`, which holds every symbol of its own,
+/// the symbols it lacks going to a new dead state after the others
+pub fn unify(dfa: &dfa::DFA, alphabet: &[char]) -> dfa::DFA {
+
+    let columns: Vec<Option<usize>> = alphabet.iter().map(|c| dfa.alphabet.iter().position(|a| a == c)).collect();
+    let dead = dfa.transitions.len() + 1;
+
+    let mut transitions: Vec<Vec<usize>> = dfa.transitions.iter()
+                                              .map(|row| columns.iter().map(|c| c.map_or(dead, |c| row[c])).collect())
+                                              .collect();
+    if columns.contains(&None) {
+        transitions.push(vec![dead; alphabet.len()]);
+    }
+
+    dfa::DFA{alphabet: alphabet.to_vec(), start: dfa.start, accept: dfa.accept.clone(), transitions}
+}
+
+// *********************************************************************
+/// The DFA accepting the strings over both alphabets that the operation
+/// keeps from two validated DFAs
+///
+/// The alphabet is the first DFA's symbols followed by those only the
+/// second has. The pairs are numbered in the order they are reached, so
+/// the start state is state 1.
+pub fn product(a: &dfa::DFA, b: &dfa::DFA, operation: Operation) -> Box<dfa::DFA> {
+
+    let mut alphabet = a.alphabet.clone();
+    alphabet.extend(b.alphabet.iter().filter(|c| !a.alphabet.contains(c)));
+    let (a, b) = (unify(a, &alphabet), unify(b, &alphabet));
+
+    let mut result = Box::new(dfa::DFA{alphabet, start: 1, accept: vec!(), transitions: vec!()});
+
+    // Pairs of states (1 relative) already given a number (0 relative)
+    let mut numbers: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut pairs: Vec<(usize, usize)> = vec![(a.start, b.start)];
+    numbers.insert(pairs[0], 0);
+
+    // Pairs are numbered in the order they are found, so walking the list
+    // while it grows visits each one exactly once
+    let mut current = 0;
+    while current < pairs.len() {
+
+        let (p, q) = pairs[current];
+        let mut row: Vec<usize> = Vec::new();
+
+        for c in 0..result.alphabet.len() {
+            let next = (a.transitions[p - 1][c], b.transitions[q - 1][c]);
+            let number = match numbers.get(&next) {
+                Some(n) => *n,
+                None => {
+                    pairs.push(next);
+                    numbers.insert(next, pairs.len() - 1);
+                    pairs.len() - 1
+                }
+            };
+            row.push(number + 1);
+        }
+
+        if operation.accepts(a.accept.contains(&p), b.accept.contains(&q)) {
+            result.accept.push(current + 1);
+        }
+
+        result.transitions.push(row);
+        current += 1;
+    }
+
+    result
+}
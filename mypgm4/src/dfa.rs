@@ -58,7 +58,6 @@ impl DFA {
     /// 
     /// Load the .yaml file specified into a DFA structure
     /// on the heap and return a point to it via a Box.
-
     pub fn new_from_file(filename: &str) -> Box<DFA> {
 
         let f = std::fs::File::open(filename)
@@ -88,7 +87,7 @@ impl DFA {
         // Validate that all states in the transition table are valid
         for (rnum, row) in self.transitions.iter().enumerate() {
             for (cnum, pda) in row.iter().enumerate() {
-                if pda.state as usize >  self.transitions.len() {
+                if pda.state >  self.transitions.len() {
                     return Err(format!("Invalid transition state({:?}) in row {}, column {}",
                                         pda, rnum + 1, cnum + 1 ))
                 }
@@ -96,12 +95,12 @@ impl DFA {
         }
 
         // The start and accept states must be valid
-        if self.start as usize > self.transitions.len() {
+        if self.start > self.transitions.len() {
             return Err(format!("Start state({}), is not valid", self.start))
         }
 
         for acc_state in self.accept.iter() {
-            if *acc_state as usize  > self.transitions.len() {
+            if *acc_state  > self.transitions.len() {
                 return Err(format!("Accept state({}), is not valid", acc_state))
             }
         }
@@ -25,11 +25,12 @@
 //! To `stderr`: Debug display of the internal graph structure
 //! 
 //! To `stdout`: Graphviz definitions of the graph structure
+#![allow(clippy::upper_case_acronyms)]
+
 use std::io;
-use std::io::prelude::*;
-use std::io::Write;
 
 mod dfa;
+mod stream;
 
 //State based representation of the PDA
 struct StateGraph {
@@ -41,7 +42,7 @@ struct StateGraph {
     start_state: usize,
 
     /// Vector of state objects
-    states: Vec<Box<State>>
+    states: Vec<State>
 
 }
 
@@ -92,8 +93,7 @@ fn get_arguments(args: std::env::Args) -> String {
 
     // Make sure only one argument was passed
     if args.len() != 2 {
-        writeln!(std::io::stderr(), "Usage: cargo run sample.yaml")
-            .unwrap();
+        eprintln!("Usage: cargo run sample.yaml");
         std::process::exit(1);
     }
     
@@ -115,7 +115,7 @@ impl StateGraph<> {
 
         // Look through the transition table building state objects
         for row in dfa.transitions.iter() {
-            let mut v = Box::new(State{accept_state: false, transitions: vec!()});
+            let mut v = State{accept_state: false, transitions: vec!()};
             for col in row {
                 let p = dfa::PDA{state: col.state-1, pop: col.pop, push: col.push};
                 v.transitions.push(p);
@@ -163,21 +163,17 @@ impl StateGraph<> {
     }
 
     //Process the input string, print out the processing steps, print whether it accepts or rejects the string
+    //The input is read in chunks, each line of it being a sentence
     fn process(&self) {
-        let stdin = io::stdin();
-        for line in stdin.lock().lines() {
-
-            // Get the line out of the Result, should never error
-            let sentence = &line.unwrap();
-            println!("Processing sentence <{}>", sentence);
-
-            match self.test_sentence(sentence) {
+        let mut matcher = stream::Matcher::new(self);
+        matcher.scan(io::stdin().lock(), |_, result| {
+            match result {
                 Ok(b) => println!("{}", 
                                   if b {"Accept"} else {"Reject"}),
                 Err(s) => println!("Error processing sentence: {}", s)
             }
-
-        }
+            Ok(())
+        }).expect("Unable to read input");
     }
 
     /// Execute the graph on a sentence
@@ -192,18 +188,18 @@ impl StateGraph<> {
         //Check if epsilon has a transition from the first state
         let n = match self.alphabet.iter().position(|v| *v == 'Ɛ') {
             Some(t) => t,
-            None => return Err(format!("Ɛ does not have a transition"))
+            None => return Err("Ɛ does not have a transition".to_string())
         };
 
         //Accept the empty string
-        if sentence.len() == 0 {
+        if sentence.is_empty() {
             return Ok(self.states[state].accept_state);
         }
 
         //Push the stack symbol onto the stack
         //Traverse to state 2 and print out the steps
         stack.push('$');
-        print!("δ(q{}, {}) → ", state+1, 'Ɛ');
+        print!("δ(q{}, Ɛ) → ", state+1);
         state = self.states[state].transitions[n].state;
         println!("(q{}), stack: {:?}", state+1, stack);
 
@@ -220,15 +216,15 @@ impl StateGraph<> {
 
                 let p = match self.alphabet.iter().position(|v| *v == 'Ɛ') {
                     Some(t) => t,
-                    None => return Err(format!("Ɛ does not have a transition"))
+                    None => return Err("Ɛ does not have a transition".to_string())
                 };
 
                 //Handle odd number palindromes
-                if (sentence.len() % 2) > 0 {
+                if !sentence.len().is_multiple_of(2) {
                     println!("here");
                     let d = match self.alphabet.iter().position(|v| *v == '0' || *v == '1') {
                         Some(t) => t,
-                        None => return Err(format!("Ɛ does not have a transition"))
+                        None => return Err("Ɛ does not have a transition".to_string())
                     };
 
                     stack.push(ch);
@@ -239,7 +235,7 @@ impl StateGraph<> {
 
 
                 //stack.push('$');
-                print!("δ(q{}, {}) → ", state+1, 'Ɛ');
+                print!("δ(q{}, Ɛ) → ", state+1);
                 state = self.states[state].transitions[p].state;
                 println!("(q{}), stack: {:?}", state+1, stack);
             }
@@ -272,12 +268,12 @@ impl StateGraph<> {
         //Check if stack symbol is only remaining character on stack, if so accept, otherwise reject. 
         if stack[stack.len()-1] == '$' {
             stack.remove(stack.len()-1);
-            print!("δ(q{}, {}) → ", state+1, 'Ɛ');
+            print!("δ(q{}, Ɛ) → ", state+1);
             state = self.states[state].transitions[n].state;
             println!("(q{}), stack: {:?}", state+1, stack);
         }
         else {
-            return Err(format!("Top of stack is not $"));
+            return Err("Top of stack is not $".to_string());
         }
 
         Ok(self.states[state].accept_state)
@@ -305,4 +301,59 @@ mod test {
         // Validate the DFA
         pda.validate().expect("Validation Failure:");
     }
-}
\ No newline at end of file
+
+    //This test is used to make sure that the stream matcher decides the same sentences however the input is split into chunks
+    #[test]
+    fn test3() {
+        let pda = dfa::DFA::new_from_file("sample.yaml");
+        let state_graph = StateGraph::new_from_dfa(&pda);
+
+        let input = "1001\n101\r\n\n10\n2\n0110";
+        let expected: Vec<(String, Result<bool, String>)> = input.lines()
+                                                                 .map(|l| (l.to_string(), state_graph.test_sentence(l)))
+                                                                 .collect();
+
+        for size in 1..=input.len() {
+            let mut matcher = stream::Matcher::new(&state_graph);
+            let mut found = Vec::new();
+            let mut collect = |sentence: &str, result| {
+                found.push((sentence.to_string(), result));
+                Ok(())
+            };
+            for chunk in input.as_bytes().chunks(size) {
+                matcher.feed(chunk, &mut collect).unwrap();
+            }
+            matcher.finish(&mut collect).unwrap();
+            assert_eq!(found, expected, "chunks of {}", size);
+        }
+
+        //A character split between chunks is read whole, and the stream can be read from any io::Read
+        let mut matcher = stream::Matcher::new(&state_graph);
+        let mut found = Vec::new();
+        matcher.scan("1Ɛ\n".as_bytes(), |sentence, result| {
+            found.push((sentence.to_string(), result));
+            Ok(())
+        }).unwrap();
+        assert_eq!(found, vec![("1Ɛ".to_string(), state_graph.test_sentence("1Ɛ"))]);
+
+        let mut matcher = stream::Matcher::new(&state_graph);
+        let mut split = Vec::new();
+        for chunk in ["1Ɛ".as_bytes()[..2].to_vec(), "1Ɛ\n".as_bytes()[2..].to_vec()].iter() {
+            matcher.feed(chunk, |sentence, result| {
+                split.push((sentence.to_string(), result));
+                Ok(())
+            }).unwrap();
+        }
+        assert_eq!(split, found);
+
+        //An error from found stops the stream
+        let mut matcher = stream::Matcher::new(&state_graph);
+        let mut count = 0;
+        let stopped = matcher.scan("1\n0\n1\n".as_bytes(), |_, _| {
+            count += 1;
+            Err(io::Error::other("stop"))
+        });
+        assert!(stopped.is_err());
+        assert_eq!(count, 1);
+    }
+}
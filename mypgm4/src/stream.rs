@@ -0,0 +1,115 @@
+//! CSIS-616 - Program #4
+//!
+//! Definition and methods associated with a matcher fed from a stream of
+//! bytes.
+//!
+//! Each line of the stream is a sentence, read the way `BufRead::lines`
+//! reads it, without its `\n` or `\r\n`. The PDA only knows when to start
+//! popping its stack once it has the length of the whole sentence, so the
+//! matcher buffers the bytes of the current line however the chunks split
+//! it, even inside a character, and runs the PDA on the line once its
+//! newline arrives, or at `finish` for a last line without one. Only the
+//! current line is held in memory, never the whole stream. Bytes that are
+//! not valid UTF-8 read as U+FFFD replacement characters, the same as
+//! `String::from_utf8_lossy`.
+
+use std::io;
+use std::io::prelude::*;
+
+use crate::StateGraph;
+
+// *********************************************************************
+/// Number of bytes read from the stream at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// *********************************************************************
+/// # Stream Matcher Structure
+pub struct Matcher<'a> {
+
+    /// The PDA being run
+    graph: &'a StateGraph,
+
+    /// The bytes of the current line read so far
+    line: Vec<u8>,
+}
+
+// *********************************************************************
+/// Implement the methods of the Matcher structure
+impl<'a> Matcher<'a> {
+
+    /// A matcher at the start of the stream
+    pub fn new(graph: &'a StateGraph) -> Matcher<'a> {
+        Matcher{graph, line: vec!()}
+    }
+
+    /// Read the next chunk of the stream, calling `found` with each line
+    /// it ends and whether the PDA accepts it, as soon as the line is
+    /// decided so the result follows the steps printed for it, and stopping
+    /// at the first error from `found`
+    pub fn feed<F>(&mut self, chunk: &[u8], mut found: F) -> io::Result<()>
+        where F: FnMut(&str, Result<bool, String>) -> io::Result<()> {
+
+        // Every piece but the last is ended by a newline
+        let mut pieces = chunk.split(|b| *b == b'\n');
+        let last = pieces.next_back().unwrap();
+        for piece in pieces {
+            self.line.extend_from_slice(piece);
+            let (sentence, result) = self.decide();
+            found(&sentence, result)?;
+        }
+        self.line.extend_from_slice(last);
+
+        Ok(())
+    }
+
+    /// The stream has ended, a last line without a newline is still a
+    /// sentence
+    pub fn finish<F>(&mut self, mut found: F) -> io::Result<()>
+        where F: FnMut(&str, Result<bool, String>) -> io::Result<()> {
+
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        let (sentence, result) = self.decide();
+        found(&sentence, result)
+    }
+
+    /// Read the whole stream in chunks, calling `found` with each sentence
+    /// and whether the PDA accepts it, and stopping at the first error from
+    /// either the stream or `found`
+    pub fn scan<R, F>(&mut self, mut reader: R, mut found: F) -> io::Result<()>
+        where R: Read,
+              F: FnMut(&str, Result<bool, String>) -> io::Result<()> {
+
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e)
+            };
+            self.feed(&buffer[..n], &mut found)?;
+        }
+
+        self.finish(found)
+    }
+
+    /// Run the PDA on the buffered line, printing its steps, and start the
+    /// next line
+    fn decide(&mut self) -> (String, Result<bool, String>) {
+
+        let mut line = std::mem::take(&mut self.line);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+
+        let sentence = String::from_utf8_lossy(&line).into_owned();
+        println!("Processing sentence <{}>", sentence);
+        let result = self.graph.test_sentence(&sentence);
+
+        (sentence, result)
+    }
+
+}
@@ -0,0 +1,214 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with a DFA that reads bytes instead
+//! of characters.
+//!
+//! Every state of the character DFA keeps a state of its own, and reading
+//! a character is spelled out as the path of its UTF-8 bytes through extra
+//! states holding the part of a character read so far. A class of the
+//! alphabet becomes byte ranges rather than a list of characters, so a
+//! class as large as `[^a]` costs a handful of states, and input bytes are
+//! looked up directly instead of being decoded first.
+//!
+//! Bytes that are not valid UTF-8 are read the same way as
+//! `String::from_utf8_lossy` would decode them: each invalid byte, or
+//! valid start of a character cut short, is one U+FFFD replacement
+//! character. A character cut short by the end of the input is also one
+//! U+FFFD.
+
+use std::collections::HashMap;
+
+use crate::charclass;
+use crate::dfa::DFA;
+
+// *********************************************************************
+/// Placeholder in a row under construction for a byte that cannot continue
+/// the character, filled in once every state of the character DFA has a row
+const CUT_SHORT: usize = usize::MAX;
+
+// *********************************************************************
+/// # Byte DFA Structure
+#[derive(Debug)]
+pub struct ByteDFA {
+
+    /// Number of states made from states of the character DFA, they come
+    /// first and keep their numbers (0 relative)
+    pub characters: usize,
+
+    /// State number (0 relative) for the start state
+    pub start: usize,
+
+    /// Does each state accept when the input ends there
+    pub accept: Vec<bool>,
+
+    /// Matrix of transitions, rows are states, columns the 256 byte values
+    pub transitions: Vec<Vec<usize>>,
+
+    /// For each state inside a character, the range of bytes that can
+    /// continue it and the state reached when it is cut short instead
+    pub partial: Vec<Option<(u8, u8, usize)>>,
+}
+
+// *********************************************************************
+/// Implement the methods of the ByteDFA structure
+impl ByteDFA {
+
+    /// Spell out the transitions of a character DFA as UTF-8 byte ranges
+    ///
+    /// The DFA is read from `start` to the end of the text, so `^` and `$`
+    /// work only at the very start and end of the input.
+    pub fn new_from_dfa(dfa: &DFA) -> ByteDFA {
+
+        let mut builder = Builder::new(dfa);
+        let characters = dfa.transitions.len();
+
+        for state in 0..characters {
+            let row = builder.character_row(state);
+            builder.rows[state] = row;
+        }
+
+        // A byte that cannot continue a character is read again from the
+        // state reached by the replacement character, whose row is done now
+        for n in characters..builder.rows.len() {
+            let (_, _, fallback) = builder.partial[n].unwrap();
+            for b in 0..256 {
+                if builder.rows[n][b] == CUT_SHORT {
+                    builder.rows[n][b] = builder.rows[fallback][b];
+                }
+            }
+        }
+
+        let mut accept = vec![false; characters];
+        for s in dfa.accept.iter() {
+            accept[*s - 1] = true;
+        }
+        for n in characters..builder.rows.len() {
+            let (_, _, fallback) = builder.partial[n].unwrap();
+            accept.push(accept[fallback]);
+        }
+
+        ByteDFA{characters, start: dfa.start - 1, accept, transitions: builder.rows, partial: builder.partial}
+    }
+
+    /// Does the DFA accept the whole input
+    #[cfg(test)]
+    pub fn is_match(&self, input: &[u8]) -> bool {
+        let state = input.iter().fold(self.start, |s, b| self.transitions[s][*b as usize]);
+        self.accept[state]
+    }
+
+}
+
+// *********************************************************************
+/// Working storage while the byte DFA is built
+struct Builder<'a> {
+
+    /// The character DFA being spelled out
+    dfa: &'a DFA,
+
+    /// Sorted code points at which the column of a character may change
+    bounds: Vec<u32>,
+
+    /// Rows of the byte DFA so far
+    rows: Vec<Vec<usize>>,
+
+    /// Continuation range and cut short state of each row
+    partial: Vec<Option<(u8, u8, usize)>>,
+
+    /// Rows inside a character already made, so equal ones are shared
+    numbers: HashMap<(Vec<usize>, usize), usize>,
+}
+
+// *********************************************************************
+/// Implement the methods of the Builder structure
+impl<'a> Builder<'a> {
+
+    /// Empty rows for the states of the character DFA
+    fn new(dfa: &'a DFA) -> Builder<'a> {
+
+        let mut bounds: Vec<u32> = dfa.alphabet.iter()
+                                      .flat_map(|class| class.ranges().iter().map(|(lo, _)| *lo as u32))
+                                      .collect();
+        bounds.sort();
+        bounds.dedup();
+
+        let states = dfa.transitions.len();
+        Builder{dfa, bounds, rows: vec![vec!(); states], partial: vec![None; states], numbers: HashMap::new()}
+    }
+
+    /// The state (0 relative) the character DFA moves to from `state` on
+    /// the character with code point `cp`
+    fn target(&self, state: usize, cp: u32) -> usize {
+        let ch = std::char::from_u32(cp).expect("UTF-8 ranges hold no surrogates");
+        self.dfa.transitions[state][charclass::column(&self.dfa.alphabet, ch)] - 1
+    }
+
+    /// Do all of the code points from `first` to `last` share a column
+    fn uniform(&self, first: u32, last: u32) -> bool {
+        let after = self.bounds.partition_point(|b| *b <= first);
+        self.bounds.get(after).is_none_or(|b| *b > last)
+    }
+
+    /// The row of a state of the character DFA, one entry per first byte
+    fn character_row(&mut self, state: usize) -> Vec<usize> {
+
+        let replaced = self.target(state, 0xFFFD);
+        let mut row = vec![replaced; 256];
+
+        for b in 0..=0xFFu32 {
+            row[b as usize] = match b {
+                0x00..=0x7F => self.target(state, b),
+                0xC2..=0xDF => self.inside(state, (b & 0x1F) << 6, 1, 0x80, 0xBF),
+                0xE0 => self.inside(state, 0, 2, 0xA0, 0xBF),
+                0xED => self.inside(state, 0xD000, 2, 0x80, 0x9F),
+                0xE1..=0xEF => self.inside(state, (b & 0x0F) << 12, 2, 0x80, 0xBF),
+                0xF0 => self.inside(state, 0, 3, 0x90, 0xBF),
+                0xF4 => self.inside(state, 0x100000, 3, 0x80, 0x8F),
+                0xF1..=0xF3 => self.inside(state, (b & 0x07) << 18, 3, 0x80, 0xBF),
+                _ => replaced
+            };
+        }
+
+        row
+    }
+
+    /// The state inside a character read from `state`, with `base` holding
+    /// the bits of the bytes read and `left` continuation bytes to go, the
+    /// next of which must be from `lo` to `hi`
+    fn inside(&mut self, state: usize, base: u32, left: u32, lo: u32, hi: u32) -> usize {
+
+        let shift = 6 * (left - 1);
+        let first = base | ((lo & 0x3F) << shift);
+        let last = base | ((hi & 0x3F) << shift) | ((1 << shift) - 1);
+
+        // When one column holds the whole range every byte leads to the
+        // same place, so it is only worked out once
+        let uniform = self.uniform(first, last);
+        let mut shared: Option<usize> = None;
+
+        let mut row = vec![CUT_SHORT; 256];
+        for b in lo..=hi {
+            let cp = base | ((b & 0x3F) << shift);
+            row[b as usize] = match shared {
+                Some(next) => next,
+                None if left == 1 => self.target(state, cp),
+                None => self.inside(state, cp, left - 1, 0x80, 0xBF)
+            };
+            if uniform {
+                shared = Some(row[b as usize]);
+            }
+        }
+
+        let fallback = self.target(state, 0xFFFD);
+        let key = (row, fallback);
+        if let Some(n) = self.numbers.get(&key) {
+            return *n;
+        }
+
+        self.rows.push(key.0.clone());
+        self.partial.push(Some((lo as u8, hi as u8, fallback)));
+        self.numbers.insert(key, self.rows.len() - 1);
+        self.rows.len() - 1
+    }
+
+}
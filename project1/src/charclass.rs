@@ -0,0 +1,315 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with character classes.
+//!
+//! A class is kept as a sorted list of inclusive character ranges that
+//! neither overlap nor touch, so two classes holding the same characters
+//! always compare equal.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+// *********************************************************************
+/// The largest Unicode scalar value
+const MAX: char = '\u{10FFFF}';
+
+// *********************************************************************
+/// # Character Class Structure
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CharClass {
+
+    /// Sorted, disjoint, non adjacent inclusive ranges
+    ranges: Vec<(char, char)>,
+}
+
+// *********************************************************************
+/// The character following `c`, skipping the surrogate gap
+fn next_char(c: char) -> Option<char> {
+    match c {
+        '\u{D7FF}' => Some('\u{E000}'),
+        _ => std::char::from_u32(c as u32 + 1)
+    }
+}
+
+/// The character preceding `c`, skipping the surrogate gap
+fn prev_char(c: char) -> Option<char> {
+    match c {
+        '\u{E000}' => Some('\u{D7FF}'),
+        '\0' => None,
+        _ => std::char::from_u32(c as u32 - 1)
+    }
+}
+
+// *********************************************************************
+/// Implement the methods of the CharClass structure
+impl CharClass {
+
+    /// Create a class from ranges in any order
+    pub fn new(mut ranges: Vec<(char, char)>) -> CharClass {
+
+        ranges.sort();
+
+        // Merge ranges that overlap or sit next to each other
+        let mut merged: Vec<(char, char)> = Vec::new();
+        for (lo, hi) in ranges {
+            if let Some(last) = merged.last_mut() {
+                if next_char(last.1).is_none_or(|n| lo <= n) {
+                    if hi > last.1 {
+                        last.1 = hi;
+                    }
+                    continue;
+                }
+            }
+            merged.push((lo, hi));
+        }
+
+        CharClass{ranges: merged}
+    }
+
+    /// Class holding a single character
+    pub fn single(c: char) -> CharClass {
+        CharClass{ranges: vec![(c, c)]}
+    }
+
+    /// `\d`: the ASCII digits
+    pub fn digit() -> CharClass {
+        CharClass::new(vec![('0', '9')])
+    }
+
+    /// `\w`: ASCII letters, digits and underscore
+    pub fn word() -> CharClass {
+        CharClass::new(vec![('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')])
+    }
+
+    /// `\s`: ASCII whitespace
+    pub fn space() -> CharClass {
+        CharClass::new(vec![('\t', '\r'), (' ', ' ')])
+    }
+
+    /// `.`: every character but newline
+    pub fn dot() -> CharClass {
+        CharClass::single('\n').negate()
+    }
+
+    /// `.` with the s flag: every character
+    pub fn any() -> CharClass {
+        CharClass::new(vec![('\0', MAX)])
+    }
+
+    /// Is the character in the class
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges
+            .binary_search_by(|r| {
+                if r.1 < c {
+                    std::cmp::Ordering::Less
+                } else if r.0 > c {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    /// Every character not in this class
+    pub fn negate(&self) -> CharClass {
+
+        let mut ranges: Vec<(char, char)> = Vec::new();
+        let mut lo = Some('\0');
+
+        for (start, end) in self.ranges.iter() {
+            if let Some(l) = lo {
+                if l < *start {
+                    ranges.push((l, prev_char(*start).unwrap()));
+                }
+            }
+            lo = next_char(*end);
+        }
+
+        if let Some(l) = lo {
+            ranges.push((l, MAX));
+        }
+
+        CharClass{ranges}
+    }
+
+    /// Every character in either class
+    pub fn union(&self, other: &CharClass) -> CharClass {
+        let mut ranges = self.ranges.clone();
+        ranges.extend_from_slice(&other.ranges);
+        CharClass::new(ranges)
+    }
+
+    /// The class with every case variant of its characters added
+    ///
+    /// Uses simple case folding, so `k` also brings in `K` and the Kelvin
+    /// sign, but `ß` does not bring in `SS`.
+    pub fn case_fold(&self) -> CharClass {
+
+        let mut ranges = self.ranges.clone();
+        for orbit in case_orbits().iter() {
+            if orbit.iter().any(|c| self.contains(*c)) {
+                ranges.extend(orbit.iter().map(|c| (*c, *c)));
+            }
+        }
+
+        CharClass::new(ranges)
+    }
+
+    /// The sorted, disjoint inclusive ranges making up the class
+    pub fn ranges(&self) -> &[(char, char)] {
+        &self.ranges
+    }
+
+    /// The first character of the class, used to stand for all of them
+    pub fn first(&self) -> Option<char> {
+        self.ranges.first().map(|r| r.0)
+    }
+
+}
+
+// *********************************************************************
+/// The character a character folds to, when changing case gives a single
+/// character
+fn fold(c: char) -> char {
+
+    fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None
+        }
+    }
+
+    let upper = single(c.to_uppercase()).unwrap_or(c);
+    single(upper.to_lowercase()).unwrap_or(c)
+}
+
+/// Every set of two or more characters that fold to the same character,
+/// worked out once from the standard library's case mappings
+fn case_orbits() -> &'static Vec<Vec<char>> {
+
+    static ORBITS: OnceLock<Vec<Vec<char>>> = OnceLock::new();
+
+    ORBITS.get_or_init(|| {
+        let mut orbits: HashMap<char, Vec<char>> = HashMap::new();
+        for c in (0..=MAX as u32).filter_map(std::char::from_u32) {
+            let folded = fold(c);
+            if folded != c {
+                orbits.entry(folded).or_insert_with(|| vec![folded]).push(c);
+            }
+        }
+        orbits.into_values().collect()
+    })
+}
+
+// *********************************************************************
+/// Split the characters used by a set of classes into the coarsest list
+/// of disjoint classes such that each of them is either entirely inside
+/// or entirely outside every original class.
+///
+/// These become the columns of a transition table. Characters outside
+/// every original class make up the last class, "other", so the table has
+/// a column for every character.
+pub fn partition(classes: &[CharClass]) -> Vec<CharClass> {
+
+    // Every place where membership of some class may change
+    let mut bounds: Vec<u32> = Vec::new();
+    for class in classes {
+        for (lo, hi) in class.ranges.iter() {
+            bounds.push(*lo as u32);
+            bounds.push(*hi as u32 + 1);
+        }
+    }
+    bounds.sort();
+    bounds.dedup();
+
+    // Group the pieces between bounds by which classes hold them, keeping
+    // the groups in the order they are first seen
+    let mut order: Vec<Vec<bool>> = Vec::new();
+    let mut groups: HashMap<Vec<bool>, Vec<(char, char)>> = HashMap::new();
+
+    for pair in bounds.windows(2) {
+
+        // Pieces made only of surrogates hold no characters
+        let lo = match std::char::from_u32(pair[0]).or_else(|| std::char::from_u32(0xE000)) {
+            Some(c) if (c as u32) < pair[1] => c,
+            _ => continue
+        };
+        let hi = std::char::from_u32(pair[1] - 1).unwrap_or('\u{D7FF}');
+
+        let signature: Vec<bool> = classes.iter().map(|c| c.contains(lo)).collect();
+        if !signature.iter().any(|b| *b) {
+            continue;
+        }
+
+        if !groups.contains_key(&signature) {
+            order.push(signature.clone());
+        }
+        groups.entry(signature).or_default().push((lo, hi));
+    }
+
+    let mut pieces: Vec<CharClass> = order.iter()
+                                          .map(|s| CharClass::new(groups[s].clone()))
+                                          .collect();
+
+    let other = classes.iter()
+                       .fold(CharClass{ranges: vec!()}, |all, c| all.union(c))
+                       .negate();
+    if other.first().is_some() {
+        pieces.push(other);
+    }
+
+    pieces
+}
+
+// *********************************************************************
+/// The column of the class holding `c` in an alphabet made by `partition`
+pub fn column(alphabet: &[CharClass], c: char) -> usize {
+    alphabet.iter()
+            .position(|class| class.contains(c))
+            .expect("the alphabet covers every character")
+}
+
+// *********************************************************************
+/// Write a character so that it reads back the same inside a class
+fn write_char(f: &mut fmt::Formatter<'_>, c: char) -> fmt::Result {
+    match c {
+        '[' | ']' | '-' | '^' => write!(f, "\\{}", c),
+        '\0' => write!(f, "\\u{{0}}"),
+        _ => write!(f, "{}", c.escape_debug())
+    }
+}
+
+// *********************************************************************
+/// Display the class in regex bracket notation, or as the bare character
+/// when it holds only one. A class made of fewer ranges when negated, like
+/// the "other" class, is written negated.
+impl fmt::Display for CharClass {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+
+        if self.ranges.len() == 1 && self.ranges[0].0 == self.ranges[0].1 {
+            return write!(f, "{}", self.ranges[0].0.escape_debug());
+        }
+
+        let negated = self.negate();
+        let ranges = if !negated.ranges.is_empty() && negated.ranges.len() < self.ranges.len() {
+            write!(f, "[^")?;
+            negated.ranges
+        } else {
+            write!(f, "[")?;
+            self.ranges.clone()
+        };
+
+        for (lo, hi) in ranges.iter() {
+            write_char(f, *lo)?;
+            if lo != hi {
+                write!(f, "-")?;
+                write_char(f, *hi)?;
+            }
+        }
+        write!(f, "]")
+    }
+
+}
@@ -0,0 +1,68 @@
+//! CSIS-616 - Project #1
+//!
+//! Generate a standalone Rust module from the DFA of a regex.
+//!
+//! The module has no dependencies and a single entry point,
+//! `pub fn accepts(input: &str) -> bool`, made of a `match` on the state
+//! holding a `match` on the character. Each class of the alphabet becomes
+//! one arm listing its ranges, except the last, which takes every other
+//! character with `_`.
+
+use std::fmt::Write;
+
+use crate::dfa::DFA;
+
+// *********************************************************************
+/// Return the Rust source of a module accepting the same strings as the
+/// DFA, `regex` is quoted in the module comment
+pub fn rust_source(dfa: &DFA, regex: &str) -> String {
+
+    let mut code = String::new();
+
+    writeln!(code, "//! Generated from the regex {:?}, do not edit.", regex).unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "/// Does the regex match the whole input").unwrap();
+    writeln!(code, "pub fn accepts(input: &str) -> bool {{").unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "    let mut state: usize = {};", dfa.start).unwrap();
+    writeln!(code).unwrap();
+    writeln!(code, "    for c in input.chars() {{").unwrap();
+    writeln!(code, "        state = match state {{").unwrap();
+
+    for (rnum, row) in dfa.transitions.iter().enumerate() {
+        writeln!(code, "            {} => match c {{", rnum + 1).unwrap();
+        for (cnum, (class, state)) in dfa.alphabet.iter().zip(row.iter()).enumerate() {
+            if cnum + 1 == dfa.alphabet.len() {
+                writeln!(code, "                _ => {},", state).unwrap();
+            } else {
+                let arms: Vec<String> = class.ranges()
+                                             .iter()
+                                             .map(|(lo, hi)| if lo == hi {
+                                                 format!("{:?}", lo)
+                                             } else {
+                                                 format!("{:?}..={:?}", lo, hi)
+                                             })
+                                             .collect();
+                writeln!(code, "                {} => {},", arms.join(" | "), state).unwrap();
+            }
+        }
+        writeln!(code, "            }},").unwrap();
+    }
+
+    writeln!(code, "            _ => unreachable!(),").unwrap();
+    writeln!(code, "        }};").unwrap();
+    writeln!(code, "    }}").unwrap();
+    writeln!(code).unwrap();
+
+    // matches! needs at least one pattern, with no accept states nothing is accepted
+    if dfa.accept.is_empty() {
+        writeln!(code, "    let _ = state;").unwrap();
+        writeln!(code, "    false").unwrap();
+    } else {
+        writeln!(code, "    matches!(state, {})",
+                 dfa.accept.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(" | ")).unwrap();
+    }
+    writeln!(code, "}}").unwrap();
+
+    code
+}
@@ -0,0 +1,344 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with Brzozowski derivatives of
+//! regular expressions.
+//!
+//! The derivative of an expression r with respect to a character c is an
+//! expression matching every s such that r matches cs. A text is accepted
+//! when the expression left after taking the derivative for each of its
+//! characters in turn matches the empty string.
+//!
+//! The smart constructors keep expressions in a normal form (`|` is
+//! flattened, sorted and without duplicates, concatenation nests to the
+//! right, and ∅ and ε are simplified away). That keeps the number of
+//! distinct derivatives finite, so they can also be used as the states of
+//! a DFA.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+
+use crate::charclass::{self, CharClass};
+use crate::dfa::DFA;
+use crate::regex::Regex;
+
+// *********************************************************************
+/// # Derivative Expression
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Expr {
+
+    /// ∅, matches nothing
+    Empty,
+
+    /// ε, matches only the empty string
+    Epsilon,
+
+    /// Any one character of the class
+    Class(CharClass),
+
+    /// The first expression followed by the second
+    Concat(Box<Expr>, Box<Expr>),
+
+    /// Any one of two or more expressions, kept sorted
+    Alt(Vec<Expr>),
+
+    /// Zero or more repetitions
+    Star(Box<Expr>),
+}
+
+// *********************************************************************
+/// Implement the methods of the Expr structure
+impl Expr {
+
+    /// Convert a parsed regex, writing out counted repetitions and
+    /// dropping groups
+    ///
+    /// Panics if the regex uses `^` or `$`, which derivatives of a single
+    /// expression cannot express.
+    pub fn new_from_regex(regex: &Regex) -> Expr {
+
+        match regex {
+            Regex::Literal(c) => Expr::Class(CharClass::single(*c)),
+            Regex::Look(_) => panic!("anchors are not supported by derivatives"),
+            Regex::Class(class) => Expr::Class(class.clone()),
+            Regex::Concat(items) => {
+                items.iter()
+                     .rev()
+                     .fold(Expr::Epsilon, |rest, r| Expr::concat(Expr::new_from_regex(r), rest))
+            },
+            Regex::Alternation(items) => Expr::alt(items.iter().map(Expr::new_from_regex).collect()),
+            Regex::Star(inner) => Expr::star(Expr::new_from_regex(inner)),
+            Regex::Plus(inner) => {
+                let r = Expr::new_from_regex(inner);
+                Expr::concat(r.clone(), Expr::star(r))
+            },
+            Regex::Optional(inner) => Expr::alt(vec![Expr::new_from_regex(inner), Expr::Epsilon]),
+            Regex::Repeat(inner, min, max) => {
+                let r = Expr::new_from_regex(inner);
+
+                // `r{2,4}` becomes `rr(r(r)?)?` and `r{2,}` becomes `rrr*`
+                let tail = match max {
+                    None => Expr::star(r.clone()),
+                    Some(m) => (*min..*m).fold(Expr::Epsilon, |rest, _| {
+                        Expr::alt(vec![Expr::concat(r.clone(), rest), Expr::Epsilon])
+                    })
+                };
+                (0..*min).fold(tail, |rest, _| Expr::concat(r.clone(), rest))
+            },
+            Regex::Group(_, inner) => Expr::new_from_regex(inner)
+        }
+    }
+
+    /// Smart constructor for `a` followed by `b`
+    pub fn concat(a: Expr, b: Expr) -> Expr {
+        match (a, b) {
+            (Expr::Empty, _) | (_, Expr::Empty) => Expr::Empty,
+            (Expr::Epsilon, r) | (r, Expr::Epsilon) => r,
+            // (ab)c is kept as a(bc)
+            (Expr::Concat(x, y), r) => Expr::concat(*x, Expr::concat(*y, r)),
+            (x, y) => Expr::Concat(Box::new(x), Box::new(y))
+        }
+    }
+
+    /// Smart constructor for any one of the expressions
+    ///
+    /// Nested alternatives are flattened, ∅ is dropped, the classes are
+    /// merged into one and the rest are sorted without duplicates.
+    pub fn alt(items: Vec<Expr>) -> Expr {
+
+        let mut flat: Vec<Expr> = Vec::new();
+        let mut class: Option<CharClass> = None;
+
+        let mut pending = items;
+        while let Some(item) = pending.pop() {
+            match item {
+                Expr::Empty => (),
+                Expr::Alt(inner) => pending.extend(inner),
+                Expr::Class(c) => {
+                    class = Some(match class {
+                        Some(k) => k.union(&c),
+                        None => c
+                    });
+                },
+                r => flat.push(r)
+            }
+        }
+
+        if let Some(c) = class {
+            flat.push(Expr::Class(c));
+        }
+        flat.sort();
+        flat.dedup();
+
+        match flat.len() {
+            0 => Expr::Empty,
+            1 => flat.remove(0),
+            _ => Expr::Alt(flat)
+        }
+    }
+
+    /// Smart constructor for zero or more repetitions
+    pub fn star(r: Expr) -> Expr {
+        match r {
+            Expr::Empty | Expr::Epsilon => Expr::Epsilon,
+            Expr::Star(_) => r,
+            r => Expr::Star(Box::new(r))
+        }
+    }
+
+    /// Does the expression match the empty string
+    pub fn nullable(&self) -> bool {
+        match self {
+            Expr::Empty | Expr::Class(_) => false,
+            Expr::Epsilon | Expr::Star(_) => true,
+            Expr::Concat(a, b) => a.nullable() && b.nullable(),
+            Expr::Alt(items) => items.iter().any(|r| r.nullable())
+        }
+    }
+
+    /// The derivative with respect to `c`
+    pub fn derivative(&self, c: char) -> Expr {
+        match self {
+            Expr::Empty | Expr::Epsilon => Expr::Empty,
+            Expr::Class(class) => if class.contains(c) { Expr::Epsilon } else { Expr::Empty },
+            Expr::Concat(a, b) => {
+                let first = Expr::concat(a.derivative(c), (**b).clone());
+                if a.nullable() {
+                    Expr::alt(vec![first, b.derivative(c)])
+                } else {
+                    first
+                }
+            },
+            Expr::Alt(items) => Expr::alt(items.iter().map(|r| r.derivative(c)).collect()),
+            Expr::Star(r) => Expr::concat(r.derivative(c), self.clone())
+        }
+    }
+
+    /// Every class used by the expression
+    fn classes(&self, classes: &mut Vec<CharClass>) {
+        match self {
+            Expr::Empty | Expr::Epsilon => (),
+            Expr::Class(class) => {
+                if !classes.contains(class) {
+                    classes.push(class.clone());
+                }
+            },
+            Expr::Concat(a, b) => {
+                a.classes(classes);
+                b.classes(classes);
+            },
+            Expr::Alt(items) => items.iter().for_each(|r| r.classes(classes)),
+            Expr::Star(r) => r.classes(classes)
+        }
+    }
+
+    /// Decide whether the whole sentence is accepted, printing each
+    /// derivative taken
+    pub fn test_sentence(&self, sentence: &str) -> bool {
+
+        let mut expr = self.clone();
+
+        for ch in sentence.chars() {
+            let next = expr.derivative(ch);
+            println!("δ({}, {}) → ({})", expr, ch, next);
+            expr = next;
+
+            // Nothing can be matched from here on
+            if expr == Expr::Empty {
+                return false;
+            }
+        }
+
+        expr.nullable()
+    }
+
+    /// Accept or reject each line of stdin
+    pub fn process(&self) {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+
+            // Get the line out of the Result, should never error
+            let sentence = &line.unwrap();
+            println!("Processing sentence <{}>", sentence);
+
+            println!("{}", if self.test_sentence(sentence) {"Accept"} else {"Reject"});
+        }
+    }
+
+}
+
+// *********************************************************************
+/// Implement building a DFA from derivatives
+impl DFA {
+
+    /// Build the DFA whose states are the distinct derivatives of the
+    /// expression, numbered in the order they are found
+    ///
+    /// Derivatives never split the classes of the original expression, so
+    /// one character of each piece of the alphabet decides the transition
+    /// for the whole piece.
+    pub fn new_from_derivatives(expr: &Expr) -> Box<DFA> {
+
+        let mut classes: Vec<CharClass> = Vec::new();
+        expr.classes(&mut classes);
+
+        let mut dfa = Box::new(DFA{alphabet: charclass::partition(&classes),
+                                   start: 1,
+                                   start_inside: 1,
+                                   accept: vec!(),
+                                   accept_inside: vec!(),
+                                   transitions: vec!() });
+
+        let mut numbers: HashMap<Expr, usize> = HashMap::new();
+        let mut states: Vec<Expr> = vec![expr.clone()];
+        numbers.insert(expr.clone(), 0);
+
+        let mut current = 0;
+        while current < states.len() {
+
+            let mut row: Vec<usize> = Vec::new();
+
+            for class in dfa.alphabet.iter() {
+                let next = match class.first() {
+                    Some(c) => states[current].derivative(c),
+                    None => Expr::Empty
+                };
+
+                let number = match numbers.get(&next) {
+                    Some(n) => *n,
+                    None => {
+                        states.push(next.clone());
+                        numbers.insert(next, states.len() - 1);
+                        states.len() - 1
+                    }
+                };
+                row.push(number + 1);
+            }
+
+            if states[current].nullable() {
+                dfa.accept.push(current + 1);
+            }
+
+            dfa.transitions.push(row);
+            current += 1;
+        }
+
+        // Without anchors the text around a match makes no difference
+        dfa.accept_inside = dfa.accept.clone();
+
+        dfa
+    }
+
+}
+
+// *********************************************************************
+/// Precedence of an expression when written out, higher binds tighter
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Alt(_) => 0,
+        Expr::Concat(..) => 1,
+        _ => 2
+    }
+}
+
+/// Write a sub expression, adding parentheses when it binds looser than
+/// its surroundings
+fn write_sub(f: &mut fmt::Formatter<'_>, expr: &Expr, min: u8) -> fmt::Result {
+    if precedence(expr) < min {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+// *********************************************************************
+/// Display the expression in regex notation with ∅ and ε spelled out
+impl fmt::Display for Expr {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Empty => write!(f, "∅"),
+            Expr::Epsilon => write!(f, "ε"),
+            Expr::Class(class) => write!(f, "{}", class),
+            Expr::Concat(a, b) => {
+                write_sub(f, a, 2)?;
+                write_sub(f, b, 1)
+            },
+            Expr::Alt(items) => {
+                for (n, r) in items.iter().enumerate() {
+                    if n > 0 {
+                        write!(f, "|")?;
+                    }
+                    write_sub(f, r, 1)?;
+                }
+                Ok(())
+            },
+            Expr::Star(r) => {
+                write_sub(f, r, 2)?;
+                write!(f, "*")
+            }
+        }
+    }
+
+}
@@ -0,0 +1,245 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the dfa structure generated
+//! from a regular expression.
+//!
+//! The regex is compiled into an ε-NFA with Thompson's construction, or
+//! into a Glushkov automaton, and then determinized with the subset
+//! construction.
+
+use std::collections::HashMap;
+
+use crate::charclass::{self, CharClass};
+use crate::glushkov::Glushkov;
+use crate::nfa;
+
+// *********************************************************************
+/// # Deterministic Finite Automata Structure
+#[derive(Debug)]
+pub struct DFA {
+
+    /// The disjoint character classes comprising the alphabet
+    pub alphabet: Vec<CharClass>,
+
+    /// State number (1 relative) for the start state
+    pub start: usize,
+
+    /// State number (1 relative) to start from when the match begins after
+    /// a character other than newline, which differs from `start` only
+    /// when the regex uses `^`
+    pub start_inside: usize,
+
+    /// Set of accept states (1 relative)
+    pub accept: Vec<usize>,
+
+    /// Set of states (1 relative) accepting when a character other than
+    /// newline follows, which differs from `accept` only when the regex
+    /// uses `$`
+    pub accept_inside: Vec<usize>,
+
+    /// Matrix of transitions, rows are states, columns classes in the alphabet
+    pub transitions: Vec<Vec<usize>>,
+
+}
+
+// *********************************************************************
+/// Implement the methods of the DFA structure
+impl DFA {
+
+    /// Determinize an ε-NFA with the subset construction
+    ///
+    /// Every DFA state is the set of NFA states reachable on the same input.
+    /// The alphabet splits the classes used by the NFA into disjoint pieces,
+    /// so one character of each piece decides the transition for all of it.
+    pub fn new_from_nfa(nfa: &nfa::NFA) -> Box<DFA> {
+        DFA::new_from_nfa_sets(nfa).0
+    }
+
+    /// Determinize an ε-NFA, also returning the set of NFA states behind
+    /// each DFA state (0 relative)
+    pub fn new_from_nfa_sets(nfa: &nfa::NFA) -> (Box<DFA>, Vec<Vec<usize>>) {
+        DFA::subsets(charclass::partition(&nfa.classes()),
+                     [nfa.start_set(), nfa.start_set_inside()],
+                     |set, c| nfa.step(set, c),
+                     |set| nfa.is_match(set),
+                     |set| nfa.is_match_inside(set))
+    }
+
+    /// Determinize an ε-NFA that may start a match after any character
+    ///
+    /// The start state's closure is added back after every step, so the
+    /// DFA accepts wherever some match of the regex ends, not only the
+    /// matches starting at the beginning of the text.
+    pub fn new_unanchored(nfa: &nfa::NFA) -> Box<DFA> {
+        DFA::subsets(charclass::partition(&nfa.classes()),
+                     [nfa.start_set(), nfa.start_set_inside()],
+                     |set, c| {
+                         let mut next = nfa.step(set, c);
+                         next.extend(nfa.closure(&[nfa.start], Some(c)));
+                         next.sort();
+                         next.dedup();
+                         next
+                     },
+                     |set| nfa.is_match(set),
+                     |set| nfa.is_match_inside(set)).0
+    }
+
+    /// Determinize a Glushkov automaton with the subset construction
+    pub fn new_from_glushkov(glushkov: &Glushkov) -> Box<DFA> {
+        DFA::subsets(charclass::partition(&glushkov.classes()),
+                     [vec![0], vec![0]],
+                     |set, c| glushkov.step(set, c),
+                     |set| glushkov.is_match(set),
+                     |set| glushkov.is_match(set)).0
+    }
+
+    /// The DFA with the fewest states accepting the same strings
+    ///
+    /// States that cannot be reached are dropped, then the rest are split
+    /// into blocks by what they accept, and blocks are split again by the
+    /// blocks their transitions lead to until no block splits (Moore's
+    /// algorithm). Each block becomes one state, numbered in the order the
+    /// states are reached so the start state is state 1.
+    pub fn minimize(&self) -> Box<DFA> {
+
+        let n = self.transitions.len();
+
+        // States reachable from either start state, in the order they are found
+        let mut order: Vec<usize> = Vec::new();
+        let mut seen = vec![false; n];
+        for start in [self.start - 1, self.start_inside - 1] {
+            if !seen[start] {
+                seen[start] = true;
+                order.push(start);
+            }
+        }
+        let mut current = 0;
+        while current < order.len() {
+            for t in self.transitions[order[current]].iter() {
+                if !seen[t - 1] {
+                    seen[t - 1] = true;
+                    order.push(t - 1);
+                }
+            }
+            current += 1;
+        }
+
+        // Start with a block for each way of accepting
+        let mut block = vec![0; n];
+        let mut numbers: HashMap<(bool, bool), usize> = HashMap::new();
+        for s in order.iter() {
+            let key = (self.accept.contains(&(s + 1)), self.accept_inside.contains(&(s + 1)));
+            let count = numbers.len();
+            block[*s] = *numbers.entry(key).or_insert(count);
+        }
+        let mut blocks = numbers.len();
+
+        // Split blocks until the number of blocks stops growing
+        loop {
+            let mut numbers: HashMap<(usize, Vec<usize>), usize> = HashMap::new();
+            let mut next = vec![0; n];
+            for s in order.iter() {
+                let key = (block[*s], self.transitions[*s].iter().map(|t| block[t - 1]).collect());
+                let count = numbers.len();
+                next[*s] = *numbers.entry(key).or_insert(count);
+            }
+            block = next;
+            if numbers.len() == blocks {
+                break;
+            }
+            blocks = numbers.len();
+        }
+
+        let mut dfa = Box::new(DFA{alphabet: self.alphabet.clone(),
+                                   start: block[self.start - 1] + 1,
+                                   start_inside: block[self.start_inside - 1] + 1,
+                                   accept: vec!(),
+                                   accept_inside: vec!(),
+                                   transitions: vec!() });
+
+        // The first state reached in each block stands for all of it
+        for b in 0..blocks {
+            let s = *order.iter().find(|s| block[**s] == b).unwrap();
+            dfa.transitions.push(self.transitions[s].iter().map(|t| block[t - 1] + 1).collect());
+            if self.accept.contains(&(s + 1)) {
+                dfa.accept.push(b + 1);
+            }
+            if self.accept_inside.contains(&(s + 1)) {
+                dfa.accept_inside.push(b + 1);
+            }
+        }
+
+        dfa
+    }
+
+    /// The subset construction from the sets `starts` at the start of the
+    /// text and after another character, `step` gives the set reached on a
+    /// character and `is_match` and `is_match_inside` whether a set accepts
+    /// at the end of the text and before another character
+    fn subsets<S, M, I>(alphabet: Vec<CharClass>, starts: [Vec<usize>; 2],
+                        step: S, is_match: M, is_match_inside: I) -> (Box<DFA>, Vec<Vec<usize>>)
+        where S: Fn(&[usize], char) -> Vec<usize>,
+              M: Fn(&[usize]) -> bool,
+              I: Fn(&[usize]) -> bool {
+
+        let mut dfa = Box::new(DFA{alphabet,
+                                   start: 1,
+                                   start_inside: 1,
+                                   accept: vec!(),
+                                   accept_inside: vec!(),
+                                   transitions: vec!() });
+
+        // Sets of NFA states already given a DFA state number (0 relative)
+        let mut numbers: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut sets: Vec<Vec<usize>> = Vec::new();
+
+        let [start, start_inside] = starts;
+        numbers.insert(start.clone(), 0);
+        sets.push(start);
+        if !numbers.contains_key(&start_inside) {
+            numbers.insert(start_inside.clone(), 1);
+            sets.push(start_inside.clone());
+        }
+        dfa.start_inside = numbers[&start_inside] + 1;
+
+        // Sets are numbered in the order they are discovered, so walking the
+        // list while it grows visits each one exactly once
+        let mut current = 0;
+        while current < sets.len() {
+
+            let mut row: Vec<usize> = Vec::new();
+
+            for class in dfa.alphabet.iter() {
+
+                let next = match class.first() {
+                    Some(c) => step(&sets[current], c),
+                    None => vec!()
+                };
+
+                let number = match numbers.get(&next) {
+                    Some(n) => *n,
+                    None => {
+                        sets.push(next.clone());
+                        numbers.insert(next, sets.len() - 1);
+                        sets.len() - 1
+                    }
+                };
+
+                row.push(number + 1);
+            }
+
+            if is_match(&sets[current]) {
+                dfa.accept.push(current + 1);
+            }
+            if is_match_inside(&sets[current]) {
+                dfa.accept_inside.push(current + 1);
+            }
+
+            dfa.transitions.push(row);
+            current += 1;
+        }
+
+        (dfa, sets)
+    }
+
+}
@@ -0,0 +1,74 @@
+//! CSIS-616 - Project #1
+//!
+//! Decide whether two DFAs accept the same strings.
+//!
+//! The two DFAs are run side by side over an alphabet splitting the
+//! classes of both, so each class of it moves both DFAs the same way for
+//! every character in it. A breadth first search over pairs of states
+//! reaches every pair by a shortest string, so the first pair where one DFA
+//! accepts and the other rejects gives a shortest string telling them
+//! apart. If no such pair can be reached, the DFAs are equivalent.
+
+use std::collections::HashSet;
+
+use crate::charclass::{self, CharClass};
+use crate::dfa::DFA;
+
+// *********************************************************************
+/// The character standing for a class in a distinguishing string,
+/// printable ASCII when the class has some
+fn sample(class: &CharClass) -> Option<char> {
+    (b'!'..=b'~').map(char::from)
+                 .find(|c| class.contains(*c))
+                 .or_else(|| class.first())
+}
+
+// *********************************************************************
+/// A shortest string that one DFA accepts as a whole and the other
+/// rejects, and whether `a` is the one accepting it, or None when they
+/// accept the same strings
+pub fn distinguish(a: &DFA, b: &DFA) -> Option<(String, bool)> {
+
+    let alphabet = charclass::partition(&[a.alphabet.clone(), b.alphabet.clone()].concat());
+    let samples: Vec<char> = alphabet.iter().filter_map(sample).collect();
+    let columns: Vec<(usize, usize)> = samples.iter()
+                                              .map(|c| (charclass::column(&a.alphabet, *c),
+                                                        charclass::column(&b.alphabet, *c)))
+                                              .collect();
+
+    // Each pair found (0 relative) with the pair and character it was
+    // first reached from
+    let mut pairs: Vec<(usize, usize)> = vec![(a.start - 1, b.start - 1)];
+    let mut parents: Vec<Option<(usize, char)>> = vec![None];
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    seen.insert(pairs[0]);
+
+    let mut current = 0;
+    while current < pairs.len() {
+
+        let (p, q) = pairs[current];
+        let accepted = a.accept.contains(&(p + 1));
+        if accepted != b.accept.contains(&(q + 1)) {
+
+            // Walk back to the start pair collecting the characters
+            let mut text: Vec<char> = Vec::new();
+            let mut n = current;
+            while let Some((parent, c)) = parents[n] {
+                text.push(c);
+                n = parent;
+            }
+            return Some((text.into_iter().rev().collect(), accepted));
+        }
+
+        for (c, (i, j)) in samples.iter().zip(columns.iter()) {
+            let next = (a.transitions[p][*i] - 1, b.transitions[q][*j] - 1);
+            if seen.insert(next) {
+                pairs.push(next);
+                parents.push(Some((current, *c)));
+            }
+        }
+        current += 1;
+    }
+
+    None
+}
@@ -0,0 +1,189 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the Glushkov (position)
+//! automaton built from a regular expression.
+//!
+//! Every occurrence of a symbol in the regex is a position. The automaton
+//! has a start state plus one state per position and no ε-transitions:
+//! the only way into a position's state is by reading a character of that
+//! position's class. Which positions can follow which is worked out from
+//! the syntax tree with the usual nullable, first, last and follow sets.
+
+use crate::charclass::CharClass;
+use crate::regex::Regex;
+
+// *********************************************************************
+/// Nullable, first and last sets of one node of the syntax tree
+struct Sets {
+    nullable: bool,
+    first: Vec<usize>,
+    last: Vec<usize>,
+}
+
+// *********************************************************************
+/// Merge two sorted sets of positions
+fn merge(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut set = a.to_vec();
+    set.extend_from_slice(b);
+    set.sort();
+    set.dedup();
+    set
+}
+
+// *********************************************************************
+/// # Glushkov Automaton Structure
+#[derive(Debug)]
+pub struct Glushkov {
+
+    /// The class read to enter each state, None for the start state 0
+    pub classes: Vec<Option<CharClass>>,
+
+    /// For each state, the sorted positions that may be read next
+    pub follow: Vec<Vec<usize>>,
+
+    /// Is each state an accept state
+    pub accept: Vec<bool>,
+}
+
+// *********************************************************************
+/// Implement the methods of the Glushkov structure
+impl Glushkov {
+
+    /// Build the position automaton for a parsed regex
+    ///
+    /// Panics if the regex uses `^` or `$`, which have no position.
+    pub fn new_from_regex(regex: &Regex) -> Glushkov {
+
+        let mut glushkov = Glushkov{classes: vec![None],
+                                    follow: vec![vec!()],
+                                    accept: vec!()};
+
+        let sets = glushkov.positions(regex);
+
+        // The start state moves to any position that can come first
+        glushkov.follow[0] = sets.first;
+
+        glushkov.accept = vec![false; glushkov.classes.len()];
+        glushkov.accept[0] = sets.nullable;
+        for p in sets.last {
+            glushkov.accept[p] = true;
+        }
+
+        glushkov
+    }
+
+    /// Number the positions of a node, filling in the follow sets inside it
+    fn positions(&mut self, regex: &Regex) -> Sets {
+        match regex {
+            Regex::Literal(c) => self.position(CharClass::single(*c)),
+            Regex::Class(class) => self.position(class.clone()),
+            Regex::Look(_) => panic!("anchors are not supported by the Glushkov construction"),
+            Regex::Concat(items) => {
+                let mut sets = Sets{nullable: true, first: vec!(), last: vec!()};
+                for item in items {
+                    let next = self.positions(item);
+                    sets = self.concat(sets, next);
+                }
+                sets
+            },
+            Regex::Alternation(items) => {
+                let mut sets = Sets{nullable: false, first: vec!(), last: vec!()};
+                for item in items {
+                    let next = self.positions(item);
+                    sets = Sets{nullable: sets.nullable || next.nullable,
+                                first: merge(&sets.first, &next.first),
+                                last: merge(&sets.last, &next.last)};
+                }
+                sets
+            },
+            Regex::Star(inner) => {
+                let sets = self.plus(inner);
+                Sets{nullable: true, ..sets}
+            },
+            Regex::Plus(inner) => self.plus(inner),
+            Regex::Optional(inner) => {
+                let sets = self.positions(inner);
+                Sets{nullable: true, ..sets}
+            },
+            Regex::Repeat(inner, min, max) => {
+
+                // Each copy of the repeated node gets positions of its own
+                let mut sets = Sets{nullable: true, first: vec!(), last: vec!()};
+                for _ in 0..*min {
+                    let next = self.positions(inner);
+                    sets = self.concat(sets, next);
+                }
+                match max {
+                    None => {
+                        let next = self.plus(inner);
+                        sets = self.concat(sets, Sets{nullable: true, ..next});
+                    },
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let next = self.positions(inner);
+                            sets = self.concat(sets, Sets{nullable: true, ..next});
+                        }
+                    }
+                }
+                sets
+            },
+            Regex::Group(_, inner) => self.positions(inner)
+        }
+    }
+
+    /// A new position reading the class
+    fn position(&mut self, class: CharClass) -> Sets {
+        self.classes.push(Some(class));
+        self.follow.push(vec!());
+        let p = self.classes.len() - 1;
+        Sets{nullable: false, first: vec![p], last: vec![p]}
+    }
+
+    /// Sets of `a` followed by `b`, anything in `b` first may follow `a` last
+    fn concat(&mut self, a: Sets, b: Sets) -> Sets {
+
+        for p in a.last.iter() {
+            self.follow[*p] = merge(&self.follow[*p], &b.first);
+        }
+
+        Sets{nullable: a.nullable && b.nullable,
+             first: if a.nullable { merge(&a.first, &b.first) } else { a.first },
+             last: if b.nullable { merge(&a.last, &b.last) } else { b.last }}
+    }
+
+    /// Sets of one or more repetitions, the node may start again after it ends
+    fn plus(&mut self, inner: &Regex) -> Sets {
+
+        let sets = self.positions(inner);
+        for p in sets.last.iter() {
+            self.follow[*p] = merge(&self.follow[*p], &sets.first);
+        }
+
+        sets
+    }
+
+    /// Every class read by some position
+    pub fn classes(&self) -> Vec<CharClass> {
+        self.classes.iter().flatten().cloned().collect()
+    }
+
+    /// The states reached from a set of states on `ch`
+    pub fn step(&self, states: &[usize], ch: char) -> Vec<usize> {
+
+        let mut next: Vec<usize> = states.iter()
+            .flat_map(|s| self.follow[*s].iter())
+            .filter(|p| self.classes[**p].as_ref().is_some_and(|c| c.contains(ch)))
+            .cloned()
+            .collect();
+
+        next.sort();
+        next.dedup();
+        next
+    }
+
+    /// Does a set of states hold an accept state
+    pub fn is_match(&self, states: &[usize]) -> bool {
+        states.iter().any(|s| self.accept[*s])
+    }
+
+}
@@ -0,0 +1,169 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the grep style search over
+//! files and directories.
+//!
+//! A line is selected when the DFA finds a match anywhere in it, the same
+//! search used by the --search option. Files are read whole and split into
+//! lines; bytes that are not valid UTF-8 are replaced before searching.
+
+use std::io;
+use std::io::prelude::*;
+use std::path::Path;
+
+use crate::StateGraph;
+
+// *********************************************************************
+/// # Grep Settings Structure
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+
+    /// -n: put the line number in front of each line
+    pub line_numbers: bool,
+
+    /// -c: print only the number of selected lines
+    pub count: bool,
+
+    /// -v: select the lines without a match
+    pub invert: bool,
+
+    /// -r: search the files inside directories, and theirs
+    pub recursive: bool,
+}
+
+// *********************************************************************
+/// # Grep Structure
+pub struct Grep<'a> {
+
+    /// The DFA searching each line
+    graph: &'a StateGraph,
+
+    /// What to select and print
+    settings: Settings,
+
+    /// Put the file name in front of each line
+    with_filenames: bool,
+}
+
+// *********************************************************************
+/// Implement the methods of the Grep structure
+impl<'a> Grep<'a> {
+
+    /// Create a search of the given paths
+    ///
+    /// File names are printed when there is more than one path or
+    /// directories are searched.
+    pub fn new(graph: &'a StateGraph, settings: Settings, paths: &[String]) -> Grep<'a> {
+        let with_filenames = paths.len() > 1 || settings.recursive;
+        Grep{graph, settings, with_filenames}
+    }
+
+    /// Search every path, or stdin when there are none
+    ///
+    /// Returns the number of lines selected and whether any path could not
+    /// be read. Problems with a path are written to stderr, and the search
+    /// stops at the first error writing to `out`.
+    pub fn run(&self, paths: &[String], out: &mut dyn Write) -> io::Result<(usize, bool)> {
+
+        if paths.is_empty() {
+            let mut bytes = Vec::new();
+            return match io::stdin().read_to_end(&mut bytes) {
+                Ok(_) => Ok((self.write_text("(standard input)", &bytes, out)?, false)),
+                Err(e) => {
+                    eprintln!("(standard input): {}", e);
+                    Ok((0, true))
+                }
+            };
+        }
+
+        let mut selected = 0;
+        let mut errors = false;
+        for path in paths {
+            let (n, e) = self.search_path(Path::new(path), out)?;
+            selected += n;
+            errors |= e;
+        }
+
+        Ok((selected, errors))
+    }
+
+    /// Search one file, or the files of a directory when recursive
+    fn search_path(&self, path: &Path, out: &mut dyn Write) -> io::Result<(usize, bool)> {
+
+        let name = path.display().to_string();
+
+        if path.is_dir() {
+            if !self.settings.recursive {
+                eprintln!("{}: Is a directory", name);
+                return Ok((0, true));
+            }
+
+            // Visit the entries in name order so the output is repeatable
+            let mut entries: Vec<_> = match std::fs::read_dir(path) {
+                Ok(dir) => dir.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+                Err(e) => {
+                    eprintln!("{}: {}", name, e);
+                    return Ok((0, true));
+                }
+            };
+            entries.sort();
+
+            let mut selected = 0;
+            let mut errors = false;
+            for entry in entries {
+
+                // Like grep -r, a link to a directory is only followed when
+                // named, so a link back up the tree cannot loop forever
+                let linked = std::fs::symlink_metadata(&entry).is_ok_and(|m| m.file_type().is_symlink());
+                if linked && entry.is_dir() {
+                    continue;
+                }
+
+                let (n, e) = self.search_path(&entry, out)?;
+                selected += n;
+                errors |= e;
+            }
+            return Ok((selected, errors));
+        }
+
+        match std::fs::read(path) {
+            Ok(bytes) => Ok((self.write_text(&name, &bytes, out)?, false)),
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                Ok((0, true))
+            }
+        }
+    }
+
+    /// Print the selected lines of one file, or their count, returning how
+    /// many were selected
+    fn write_text(&self, name: &str, bytes: &[u8], out: &mut dyn Write) -> io::Result<usize> {
+
+        let text = String::from_utf8_lossy(bytes);
+        let prefix = if self.with_filenames { format!("{}:", name) } else { String::new() };
+        let mut selected = 0;
+
+        for (n, line) in text.lines().enumerate() {
+
+            if self.graph.find(line).is_some() == self.settings.invert {
+                continue;
+            }
+            selected += 1;
+
+            if !self.settings.count {
+                if self.settings.line_numbers {
+                    writeln!(out, "{}{}:{}", prefix, n + 1, line)?;
+                } else {
+                    writeln!(out, "{}{}", prefix, line)?;
+                }
+            }
+        }
+
+        if self.settings.count {
+            writeln!(out, "{}{}", prefix, selected)?;
+        }
+
+        Ok(selected)
+    }
+
+}
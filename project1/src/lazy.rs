@@ -0,0 +1,224 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the lazy DFA.
+//!
+//! Instead of running the whole subset construction up front, DFA states
+//! (sets of ε-NFA states) are built the first time the input leads to
+//! them and cached along with the transitions taken. The cache is given a
+//! memory budget; when it fills up it is thrown away and rebuilt. If that
+//! keeps happening while matching one input, the cache is not helping, so
+//! the rest of that input is matched by simulating the ε-NFA directly.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+
+use crate::charclass::{self, CharClass};
+use crate::nfa::NFA;
+
+// *********************************************************************
+/// Marks a transition that has not been computed yet
+const UNKNOWN: usize = usize::MAX;
+
+/// Number of times the cache may be cleared while matching one input
+/// before falling back to simulating the ε-NFA
+const MAX_CLEARS: usize = 3;
+
+// *********************************************************************
+/// # Lazy DFA Statistics
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+
+    /// Transitions found already in the cache
+    pub hits: usize,
+
+    /// DFA states built (cache misses)
+    pub states_built: usize,
+
+    /// Times the cache was thrown away because it hit the budget
+    pub cache_clears: usize,
+
+    /// Inputs finished by simulating the ε-NFA instead
+    pub fallbacks: usize,
+}
+
+// *********************************************************************
+/// Display the statistics on one line
+impl fmt::Display for Stats {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cache hits: {}, states built: {}, cache clears: {}, NFA fallbacks: {}",
+                  self.hits, self.states_built, self.cache_clears, self.fallbacks)
+    }
+
+}
+
+// *********************************************************************
+/// # Lazy DFA Structure
+pub struct LazyDFA {
+
+    /// The ε-NFA being determinized
+    nfa: NFA,
+
+    /// The disjoint character classes comprising the alphabet
+    alphabet: Vec<CharClass>,
+
+    /// Most bytes the cached states may take
+    budget: usize,
+
+    /// Bytes taken by the cached states
+    used: usize,
+
+    /// Set of NFA states for each cached DFA state
+    sets: Vec<Vec<usize>>,
+
+    /// Cached DFA state number for each set
+    numbers: HashMap<Vec<usize>, usize>,
+
+    /// Rows are cached states, columns classes, UNKNOWN until computed
+    transitions: Vec<Vec<usize>>,
+
+    /// Is each cached state an accept state
+    accept: Vec<bool>,
+
+    /// Cache statistics
+    stats: Stats,
+}
+
+// *********************************************************************
+/// Implement the methods of the LazyDFA structure
+impl LazyDFA {
+
+    /// Create a lazy DFA for the ε-NFA that caches at most `budget` bytes
+    /// of states
+    pub fn new(nfa: NFA, budget: usize) -> LazyDFA {
+
+        let alphabet = charclass::partition(&nfa.classes());
+
+        LazyDFA{nfa,
+                alphabet,
+                budget,
+                used: 0,
+                sets: vec!(),
+                numbers: HashMap::new(),
+                transitions: vec!(),
+                accept: vec!(),
+                stats: Stats::default()}
+    }
+
+    /// The statistics gathered so far
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Approximate bytes taken by a cached state
+    fn state_size(&self, set: &[usize]) -> usize {
+        let word = std::mem::size_of::<usize>();
+        // The set is stored twice, in `sets` and as the key of `numbers`
+        2 * set.len() * word + self.alphabet.len() * word + 8 * word
+    }
+
+    /// Throw away every cached state
+    fn clear(&mut self) {
+        self.sets.clear();
+        self.numbers.clear();
+        self.transitions.clear();
+        self.accept.clear();
+        self.used = 0;
+        self.stats.cache_clears += 1;
+    }
+
+    /// The cached state number for a set, building the state if needed
+    ///
+    /// Also returns whether the cache had to be cleared to make room, which
+    /// invalidates every state number handed out before.
+    fn state_for(&mut self, set: Vec<usize>) -> (usize, bool) {
+
+        if let Some(n) = self.numbers.get(&set) {
+            return (*n, false);
+        }
+
+        let size = self.state_size(&set);
+        let cleared = self.used + size > self.budget && !self.sets.is_empty();
+        if cleared {
+            self.clear();
+        }
+
+        self.stats.states_built += 1;
+        self.used += size;
+        self.accept.push(self.nfa.is_match(&set));
+        self.transitions.push(vec![UNKNOWN; self.alphabet.len()]);
+        self.sets.push(set.clone());
+        self.numbers.insert(set, self.sets.len() - 1);
+
+        (self.sets.len() - 1, cleared)
+    }
+
+    /// Decide whether the whole text is accepted
+    pub fn is_match(&mut self, text: &str) -> bool {
+
+        let start = self.nfa.start_set();
+        let (mut state, _) = self.state_for(start);
+        let mut clears = 0;
+
+        for (i, ch) in text.char_indices() {
+
+            let col = charclass::column(&self.alphabet, ch);
+
+            let next = self.transitions[state][col];
+            if next != UNKNOWN {
+                self.stats.hits += 1;
+                state = next;
+                continue;
+            }
+
+            let set = self.nfa.step(&self.sets[state], ch);
+            let (next, cleared) = self.state_for(set);
+
+            if cleared {
+                clears += 1;
+
+                // The cache keeps filling up on this input, stop building states
+                if clears >= MAX_CLEARS {
+                    self.stats.fallbacks += 1;
+                    let set = self.sets[next].clone();
+                    return self.simulate(set, &text[i + ch.len_utf8()..]);
+                }
+            } else {
+                self.transitions[state][col] = next;
+            }
+
+            state = next;
+        }
+
+        self.accept[state]
+    }
+
+    /// Finish matching by stepping through sets of ε-NFA states
+    fn simulate(&self, mut set: Vec<usize>, rest: &str) -> bool {
+
+        for ch in rest.chars() {
+            set = self.nfa.step(&set, ch);
+            if set.is_empty() {
+                return false;
+            }
+        }
+
+        self.nfa.is_match(&set)
+    }
+
+    /// Accept or reject each line of stdin
+    pub fn process(&mut self) {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+
+            // Get the line out of the Result, should never error
+            let sentence = &line.unwrap();
+            println!("Processing sentence <{}>", sentence);
+
+            println!("{}", if self.is_match(sentence) {"Accept"} else {"Reject"});
+        }
+    }
+
+}
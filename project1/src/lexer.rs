@@ -0,0 +1,261 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the lexer generator.
+//!
+//! A lexer is an ordered list of rules, each a token name and a regex.
+//! The regexes are compiled into one DFA the same way as a regex set, and
+//! each DFA state is tagged with the first rule accepting there. The text
+//! is split by reading the longest run of characters that some rule
+//! accepts; when several rules accept that run, the one listed first wins.
+
+use std::fmt;
+use std::fmt::Write;
+
+use crate::charclass;
+use crate::dfa::DFA;
+use crate::nfa::NFA;
+use crate::regex::{self, Syntax};
+use crate::StateGraph;
+
+// *********************************************************************
+/// # Lexer Rule Structure
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+
+    /// Name given to the tokens the rule matches
+    pub name: String,
+
+    /// The regex a token has to match
+    pub pattern: String,
+}
+
+// *********************************************************************
+/// Read rules written one per line as a token name, white space and the
+/// regex making up the rest of the line
+///
+/// Blank lines and lines starting with `#` are skipped.
+pub fn parse_rules(text: &str) -> Result<Vec<Rule>, String> {
+
+    let mut rules: Vec<Rule> = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (name, pattern) = match line.split_once(char::is_whitespace) {
+            Some((name, pattern)) if !pattern.trim().is_empty() => (name, pattern.trim()),
+            _ => return Err(format!("Rule on line {} has no regex", n + 1))
+        };
+
+        rules.push(Rule{name: name.to_string(), pattern: pattern.to_string()});
+    }
+
+    Ok(rules)
+}
+
+// *********************************************************************
+/// # Token Structure
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+
+    /// Name of the rule that matched
+    pub name: &'a str,
+
+    /// The text matched
+    pub text: &'a str,
+
+    /// Line of the first character, from 1
+    pub line: usize,
+
+    /// Column of the first character in characters, from 1
+    pub column: usize,
+}
+
+// *********************************************************************
+/// Display the token with its position
+impl fmt::Display for Token<'_> {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{} {} <{}>", self.line, self.column, self.name, self.text.escape_debug())
+    }
+
+}
+
+// *********************************************************************
+/// # Lexical Error Structure
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+
+    /// The character no rule could start with
+    pub ch: char,
+
+    /// Line of the character, from 1
+    pub line: usize,
+
+    /// Column of the character in characters, from 1
+    pub column: usize,
+}
+
+// *********************************************************************
+/// Display the error with its position
+impl fmt::Display for LexError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Lexical error at {}:{}: no rule matches <{}>", self.line, self.column, self.ch.escape_debug())
+    }
+
+}
+
+// *********************************************************************
+/// # Lexer Structure
+pub struct Lexer {
+
+    /// Token name of each rule, in priority order
+    names: Vec<String>,
+
+    /// The DFA for all of the rules at once
+    dfa: Box<DFA>,
+
+    /// For each DFA state (0 relative), the first rule accepting there
+    tokens: Vec<Option<usize>>,
+
+    /// For each DFA state, can some rule still accept from there
+    live: Vec<bool>,
+}
+
+// *********************************************************************
+/// Implement the methods of the Lexer structure
+impl Lexer {
+
+    /// Compile the rules, stopping at the first one whose regex is
+    /// malformed or uses `^` or `$`
+    pub fn new(rules: &[Rule], syntax: &Syntax) -> Result<Lexer, String> {
+
+        let mut regexes = Vec::new();
+        for rule in rules {
+            let regex = regex::parse(&rule.pattern, syntax).map_err(|e| format!("Rule {}: {}", rule.name, e))?;
+            if regex.has_looks() {
+                return Err(format!("Rule {}: ^ and $ cannot be used in a lexer", rule.name));
+            }
+            regexes.push(regex);
+        }
+
+        let (nfa, accept_states) = NFA::new_from_regexes(&regexes);
+        let (dfa, sets) = DFA::new_from_nfa_sets(&nfa);
+
+        // The accept state of the first rule listed wins
+        let tokens: Vec<Option<usize>> = sets.iter()
+            .map(|set| {
+                let matched = nfa.matches(set);
+                (0..rules.len()).find(|i| matched.contains(&accept_states[*i]))
+            })
+            .collect();
+
+        // The DFA accepts exactly where some rule does, so the live states of
+        // its graph are those from which some rule can still accept
+        let live = StateGraph::new_from_dfa(&dfa).states.iter().map(|s| s.live).collect();
+
+        Ok(Lexer{names: rules.iter().map(|r| r.name.clone()).collect(), dfa, tokens, live})
+    }
+
+    /// The end (byte offset) and rule of the longest token starting at
+    /// byte offset `start`, None when no rule matches a non-empty prefix
+    fn longest_at(&self, text: &str, start: usize) -> Option<(usize, usize)> {
+
+        let mut state = self.dfa.start - 1;
+        let mut best: Option<(usize, usize)> = None;
+
+        for (i, ch) in text[start..].char_indices() {
+            state = self.dfa.transitions[state][charclass::column(&self.dfa.alphabet, ch)] - 1;
+            if !self.live[state] {
+                break;
+            }
+            if let Some(rule) = self.tokens[state] {
+                best = Some((start + i + ch.len_utf8(), rule));
+            }
+        }
+
+        best
+    }
+
+    /// Split the text into tokens, reporting each character no rule can
+    /// start with and carrying on after it
+    pub fn tokenize<'a>(&'a self, text: &'a str) -> Vec<Result<Token<'a>, LexError>> {
+
+        let mut result = Vec::new();
+        let mut pos = 0;
+        let mut line = 1;
+        let mut column = 1;
+
+        while pos < text.len() {
+
+            let end = match self.longest_at(text, pos) {
+                Some((end, rule)) => {
+                    result.push(Ok(Token{name: &self.names[rule], text: &text[pos..end], line, column}));
+                    end
+                },
+                None => {
+                    let ch = text[pos..].chars().next().unwrap();
+                    result.push(Err(LexError{ch, line, column}));
+                    pos + ch.len_utf8()
+                }
+            };
+
+            // Move the position past what was read
+            for ch in text[pos..end].chars() {
+                if ch == '\n' {
+                    line += 1;
+                    column = 1;
+                } else {
+                    column += 1;
+                }
+            }
+            pos = end;
+        }
+
+        result
+    }
+
+    /// Graphviz definition of the combined DFA with accept states labelled
+    /// by token name
+    ///
+    /// The dead state, which no rule can accept from, and the transitions
+    /// into it are left out.
+    pub fn graphviz(&self) -> String {
+
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph {{").unwrap();
+        writeln!(dot, "\trankdir=LR;").unwrap();
+        writeln!(dot, "\tnode [shape=point]; start;").unwrap();
+
+        for (n, token) in self.tokens.iter().enumerate() {
+            if let Some(rule) = token {
+                writeln!(dot, "\tnode [shape=doublecircle]; q{} [label=\"q{}\\n{}\"];",
+                         n + 1, n + 1, self.names[*rule].replace('\\', "\\\\").replace('"', "\\\"")).unwrap();
+            }
+        }
+
+        writeln!(dot, "\tnode [shape=circle];").unwrap();
+        writeln!(dot, "\tstart -> q{}", self.dfa.start).unwrap();
+
+        for (n, row) in self.dfa.transitions.iter().enumerate() {
+            if !self.live[n] {
+                continue;
+            }
+            for (i, class) in self.dfa.alphabet.iter().enumerate() {
+                if self.live[row[i] - 1] {
+                    let label = class.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+                    writeln!(dot, "\tq{} -> q{} [label=\"{}\"]", n + 1, row[i], label).unwrap();
+                }
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+}
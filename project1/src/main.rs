@@ -22,42 +22,44 @@
 //! 
 //! To `stdout`: Graphviz definitions of the graph structure
 
-
+#![allow(clippy::upper_case_acronyms)]
 
 use std::io;
 use std::io::prelude::*;
-use std::io::Write;
-
-
-// *********************************************************************
-/// # Deterministic Finite Automata Structure
-struct DFA {
-
-    /// The set of characters comprising the alphabet
-    alphabet: Vec<char>,
-
-    /// State number (1 relative) for the start state
-    start: usize,
-
-    /// Set of accept states (1 relative)
-    accept: Vec<usize>, //will need to be Vec<usize> when multiple accept states are implemented
-
-    /// Matrix of transitions, rows are states, columns characters in the alphabet
-    transitions: Vec<Vec<usize>>,
-    
-}
+use std::io::IsTerminal;
+use std::ops::Range;
+
+mod bytedfa;
+mod charclass;
+mod codegen;
+mod derivative;
+mod dfa;
+mod equiv;
+mod glushkov;
+mod grep;
+mod lazy;
+mod lexer;
+mod nfa;
+mod pikevm;
+mod regex;
+mod regexset;
+mod simplify;
+mod stream;
 
 //State based representation of the DFA version of the RegEx
 struct StateGraph {
 
-    /// The set of characters comprising the alphabet
-    alphabet: Vec<char>,
+    /// The disjoint character classes comprising the alphabet
+    alphabet: Vec<charclass::CharClass>,
 
     /// State number for the start state
     start_state: usize,
 
+    /// State number to start from after another character of the line
+    start_inside: usize,
+
     /// Vector of state objects
-    states: Vec<Box<State>>
+    states: Vec<State>
 
 }
 
@@ -67,272 +69,506 @@ struct State {
 	//Is this an accept state
 	accept_state: bool,
 
-	//Set of transitions
-	transitions: Vec<usize>
+	//Does this state accept when another character of the line follows
+	accept_inside: bool,
+
+	//Set of transitions, the states reached on each class of the alphabet.
+	// A graph made from a DFA has exactly one state for every class
+	transitions: Vec<Vec<usize>>,
+
+	//Can an accept state still be reached from this state
+	live: bool
 }
 
-struct Transitions {
-	chars: char,
+// *********************************************************************
+/// What to do with each line of input
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+
+    /// Accept or reject the whole line, printing the steps taken
+    Test,
+
+    /// Print lines containing a match with the leftmost-longest match highlighted
+    Search,
+
+    /// Print lines containing a match with every non-overlapping match highlighted
+    SearchAll,
+
+    /// Print which of several patterns accept each line
+    Set,
+
+    /// Split the input into tokens with the rules of a lexer
+    Lex,
+
+    /// Write the DFA as a standalone Rust module
+    Codegen,
+
+    /// Print the lines of files containing a match, like grep
+    Grep,
+
+    /// Print where every match ends, reading stdin in chunks instead of lines
+    Stream,
+
+    /// Decide whether two regexes match the same strings
+    Equiv,
+
+    /// Print a smaller regex matching the same strings
+    Simplify,
+}
+
+// *********************************************************************
+/// Which matcher decides whether a line is accepted
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Engine {
+
+    /// The DFA built in full by the subset construction
+    Dfa,
+
+    /// States built on demand and cached within a memory budget
+    Lazy,
+
+    /// Brzozowski derivatives taken one character at a time
+    Derivative,
+}
+
+// *********************************************************************
+/// How the automaton is built from the RegEx
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Construction {
+
+    /// Thompson's construction, an ε-NFA with a fragment per operator
+    Thompson,
+
+    /// The Glushkov automaton, a state per symbol and no ε-transitions
+    Glushkov,
+}
+
+// *********************************************************************
+/// Settings given on the command line
+struct Options {
+
+    /// The RegEx to compile, or every pattern of a set
+    patterns: Vec<String>,
+
+    /// File holding the rules of a lexer
+    rules: String,
+
+    /// Files to read instead of stdin
+    files: Vec<String>,
+
+    /// Write the Graphviz definition of the lexer instead of tokens
+    graphviz: bool,
+
+    /// File the generated Rust module is written to
+    output: String,
+
+    /// What the grep mode selects and prints
+    grep: grep::Settings,
 
-	state: usize
+    /// Settings the RegEx is parsed with
+    syntax: regex::Syntax,
+
+    /// What to do with each line of input
+    mode: Mode,
+
+    /// Which matcher decides whether a line is accepted
+    engine: Engine,
+
+    /// How the automaton is built from the RegEx
+    construction: Construction,
+
+    /// Most bytes the lazy DFA may use for its cache
+    cache_size: usize,
 }
 
 fn main() {
 
-	//Get and validate the RegEx on the command line
-	let regex = get_regex(std::env::args());
+	//Get and validate the RegEx and options on the command line
+	let options = get_arguments(std::env::args());
+
+	//Split the input into tokens
+	if options.mode == Mode::Lex {
+	    lex(&options);
+	    return;
+	}
+
+	//Compare the languages of two regexes instead of reading any input
+	if options.mode == Mode::Equiv {
+	    equivalent(&options);
+	    return;
+	}
+
+	//Compile every pattern into one DFA telling which of them accept
+	if options.mode == Mode::Set {
+	    let set = match regexset::RegexSet::new(&options.patterns, &options.syntax) {
+	        Ok(s) => s,
+	        Err(e) => {
+	            eprintln!("{}", e);
+	            std::process::exit(1);
+	        }
+	    };
+	    eprintln!("{} patterns compiled into one DFA with {} states", options.patterns.len(), set.states());
+	    set.process();
+	    return;
+	}
+
+	//Parse the RegEx, pointing out where it is malformed if it does not parse
+	let regex = match regex::parse(&options.patterns[0], &options.syntax) {
+		Ok(r) => r,
+		Err(e) => {
+			eprintln!("{}", e);
+			std::process::exit(1);
+		}
+	};
+
+	//Rewrite the RegEx instead of reading any input
+	if options.mode == Mode::Simplify {
+	    let simpler = simplify::simplify(&regex);
+	    let written = simpler.to_string();
+	    eprintln!("Size: {} symbols ({} characters) before, {} symbols ({} characters) after",
+	              regex.size(), options.patterns[0].chars().count(), simpler.size(), written.chars().count());
+	    println!("{}", written);
+	    return;
+	}
+
+	//Anchors need the ε-NFA to decide them
+	if regex.has_looks() && (options.engine == Engine::Derivative
+	                         || options.construction == Construction::Glushkov) {
+	    eprintln!("^ and $ need the dfa or lazy engine with Thompson's construction");
+	    std::process::exit(1);
+	}
 
-	let dfa = DFA::new_from_regex(&regex);
+	//Derivatives work on the expression itself, the DFA they give is only drawn
+	if options.engine == Engine::Derivative {
+	    let expr = derivative::Expr::new_from_regex(&regex);
+	    StateGraph::new_from_dfa(&dfa::DFA::new_from_derivatives(&expr)).write_graphviz();
+	    expr.process();
+	    return;
+	}
+
+	//Build the ε-NFA
+	let nfa = nfa::NFA::new_from_regex(&regex);
+
+	//Scan stdin a chunk at a time with the DFA that starts a match anywhere
+	if options.mode == Mode::Stream {
+	    if regex.has_looks() {
+	        eprintln!("^ and $ cannot be used with --stream");
+	        std::process::exit(1);
+	    }
+	    let bytes = bytedfa::ByteDFA::new_from_dfa(&dfa::DFA::new_unanchored(&nfa));
+	    let mut matcher = stream::Matcher::new(&bytes);
+	    let stdout = io::stdout();
+	    let mut out = stdout.lock();
+	    if let Err(e) = matcher.scan(io::stdin().lock(), |end| writeln!(out, "Match ending at byte {}", end)) {
+	        eprintln!("{}", e);
+	        std::process::exit(1);
+	    }
+	    eprintln!("{} bytes read", matcher.offset());
+	    return;
+	}
+
+	//The lazy DFA only builds the states the input needs
+	if options.engine == Engine::Lazy {
+	    let mut lazy = lazy::LazyDFA::new(nfa, options.cache_size);
+	    lazy.process();
+	    eprintln!("{}", lazy.stats());
+	    return;
+	}
+
+	//Determinize the ε-NFA or the Glushkov automaton, drawing the automaton
+	// that was determinized
+	let (dfa, drawn) = match options.construction {
+	    Construction::Thompson => {
+	        let dfa = dfa::DFA::new_from_nfa(&nfa);
+	        let drawn = StateGraph::new_from_dfa(&dfa);
+	        (dfa, drawn)
+	    },
+	    Construction::Glushkov => {
+	        let glushkov = glushkov::Glushkov::new_from_regex(&regex);
+	        eprintln!("Glushkov automaton: {} states, Thompson ε-NFA: {} states",
+	                  glushkov.classes.len(), nfa.states.len());
+	        (dfa::DFA::new_from_glushkov(&glushkov), StateGraph::new_from_glushkov(&glushkov))
+	    }
+	};
+
+	//Write the DFA as Rust source instead of reading any input
+	if options.mode == Mode::Codegen {
+	    if let Err(e) = std::fs::write(&options.output, codegen::rust_source(&dfa, &options.patterns[0])) {
+	        eprintln!("Cannot write {}: {}", options.output, e);
+	        std::process::exit(1);
+	    }
+	    return;
+	}
+
+	//Keep the ε-NFA to find capture groups
+	let vm = pikevm::PikeVM::new(nfa);
 
 	//Create the dfa structure based on in RegEx entered from the command line
     let state_graph = StateGraph::new_from_dfa(&dfa);
 
 	//eprintln!("{:?}", state_graph);
 
-    state_graph.write_graphviz();
-
+	// Process through the input until end of file (cntl-z) is encountered
+	match options.mode {
+	    Mode::Test => {
+	        drawn.write_graphviz();
+	        state_graph.process(&vm);
+	    },
+	    Mode::Grep => {
+	        // Like grep, exit with 0 when a line was selected, 1 when none
+	        // was and 2 when a file could not be read or the output closed
+	        let grep = grep::Grep::new(&state_graph, options.grep.clone(), &options.files);
+	        let status = match grep.run(&options.files, &mut io::stdout().lock()) {
+	            Ok((_, true)) | Err(_) => 2,
+	            Ok((selected, false)) => if selected > 0 { 0 } else { 1 }
+	        };
+	        std::process::exit(status);
+	    },
+	    _ => state_graph.search_lines(options.mode == Mode::SearchAll)
+	}
+}
 
+// *********************************************************************
+/// Read a whole file, exiting with a message if it cannot be read
+fn read_file(file: &str) -> String {
+    match std::fs::read_to_string(file) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Cannot read {}: {}", file, e);
+            std::process::exit(1);
+        }
+    }
+}
 
-	// Process through the input until end of file (cntl-z) is encountered
-    state_graph.process();
+// *********************************************************************
+/// Build the minimal DFA of both regexes and print whether they match the
+/// same strings, or a shortest string only one of them matches, exiting
+/// with 1 when they differ
+fn equivalent(options: &Options) {
+
+    let dfas: Vec<Box<dfa::DFA>> = options.patterns.iter().map(|pattern| {
+        let regex = match regex::parse(pattern, &options.syntax) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        dfa::DFA::new_from_nfa(&nfa::NFA::new_from_regex(&regex)).minimize()
+    }).collect();
+
+    eprintln!("Minimal DFAs: {} and {} states", dfas[0].transitions.len(), dfas[1].transitions.len());
+
+    match equiv::distinguish(&dfas[0], &dfas[1]) {
+        None => println!("Equivalent"),
+        Some((text, first)) => {
+            let (accepts, rejects) = if first { (0, 1) } else { (1, 0) };
+            println!("Not equivalent: <{}> is accepted by <{}> and rejected by <{}>",
+                     text.escape_debug(), options.patterns[accepts], options.patterns[rejects]);
+            std::process::exit(1);
+        }
+    }
 }
 
 // *********************************************************************
-/// Return the RegEx passed as the first parameter
-fn get_regex(args: std::env::Args) -> String {
+/// Tokenize the input files, or stdin, with the lexer rules, printing the
+/// tokens and lexical errors in order
+fn lex(options: &Options) {
+
+    let rules = match lexer::parse_rules(&read_file(&options.rules)) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{}: {}", options.rules, e);
+            std::process::exit(1);
+        }
+    };
 
-    // Get the arguments as a vector
-    let args: Vec<String> = args.collect();
+    let lexer = match lexer::Lexer::new(&rules, &options.syntax) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if options.graphviz {
+        print!("{}", lexer.graphviz());
+        return;
+    }
 
-    // Make sure only one argument was passed
-    if args.len() != 2 {
-        writeln!(std::io::stderr(), "Usage: cargo run 'regex'")
-            .unwrap();
+    let inputs: Vec<String> = if options.files.is_empty() {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).unwrap();
+        vec![text]
+    } else {
+        options.files.iter().map(|f| read_file(f)).collect()
+    };
+
+    let mut errors = false;
+    for text in inputs.iter() {
+        for token in lexer.tokenize(text) {
+            match token {
+                Ok(t) => println!("{}", t),
+                Err(e) => {
+                    println!("{}", e);
+                    errors = true;
+                }
+            }
+        }
+    }
+
+    if errors {
         std::process::exit(1);
     }
-    
-    args[1].to_string()
-    
 }
 
 // *********************************************************************
-/// Implement the methods of the DFA structure
-impl DFA {
-
-	//Create and return a DFA on the heap
-	//Generate the DFA from the given regex
-	fn new_from_regex(regex: &str) -> Box<DFA> {
-
-		//Setup the regex as the language / alphabet of the dfa
-		//Remove any duplicate word characters
-		let mut l = regex.replace("|", "");
-		l = l.replace("+", "");
-		l = l.replace("*", "");
-
-		//Creates a language Vec<char> without the operators in it and pushing the sigma symbol for alphabet purposes
-		let mut language: Vec<char> = l.chars().collect();
-		language.sort();
-		language.dedup();
-		language.push('Σ');
-
-		let final_state = l.len()+1;
-
-		//Create a near blank dfa object, with 1 being start state, accept state being the final state
-		// which is calculated based on the length of the regex length + 1
-		let mut dfa = Box::new(DFA{alphabet: language,
-									start: 1,
-									accept: [final_state].to_vec(),
-									transitions: vec![] });
-
-
-		//Set current and next state to traverse through the graph as we create the transition matrix. 
-		let mut current_state = 1;
-		let mut next_state = 2;
-
-		//Create the Transitions Struct to save any transitions characters. These are characters that would
-		// need to be cycled back to. First character and second state will always start this off. 
-		let mut transitions: Vec<Transitions> = Vec::new();
-		let t = Transitions{chars: regex.chars().next().unwrap(),
-											state: 2};
-		transitions.push(t);
-
-		//Create a previous_char character for | and * operators
-		let mut previous_char = regex.chars().next().unwrap();
-
-		//Traverse through the regex string, reading characters and deciding what to do depending on the character.
-		for c in regex.chars() {
-			let mut states: Vec<usize> = Vec::new();
-
-
-			//Checks if previous char was a | operator. 
-			//If so, save the current character as a transition or cycle character
-			//Also fixes any previous transition state
-			if previous_char == '|' {
-				for (n, a) in dfa.alphabet.iter().enumerate() {
-					if *a == c {
-						dfa.transitions[0][n] = next_state;
-					}
-				}
-				let j = Transitions{chars: c, state: next_state};
-				transitions.push(j);
-			}
-
-			//Same as above, just with the * operator.
-			if previous_char == '*' {
-				let j = Transitions{chars: c, state: next_state};
-				transitions.push(j);
-			}
-
-			//Operator '|': Implemented - single and multiple | operators are working
-			//Multiple types of symbols are untested and could produce varying results
-			//Checks if character is | operator. If so, save the final state as an accept state, reset
-			//current state back to 1, and set previous_char as |
-			if c == '|' {
-
-				let final_bar_state = dfa.transitions.len()+1;
-
-				let mut final_bar_state_count: Vec<usize> = Vec::new();
-
-				dfa.accept.push(final_bar_state);
-				for _a in dfa.alphabet.iter() {
-					final_bar_state_count.push(final_bar_state);
-				}
-				dfa.transitions.push(final_bar_state_count);
-				current_state = 1;
-
-				previous_char = '|';
-			}
-			//Operator '+': Implemented - single works, multiple is funky, almost working
-			//Removes the previous transition matrix to remake it with updated states
-			//Fix to the multiple + operators I believe is using a for loop to go through the entire transitions vec
-			// but I have ran out of time to get that working. 
-			else if c == '+' {
-
-				dfa.transitions.remove(dfa.transitions.len()-1);
-
-				next_state -= 1;
-				current_state -= 1;
-
-				for a in dfa.alphabet.iter() {
-					if a == &previous_char {
-						states.push(next_state);
-					} else {
-						if *a == transitions[0].chars {
-							states.push(transitions[0].state);
-						} else {
-							states.push(1);
-						}
-					}
-				}
-
-				dfa.transitions.push(states);
-				next_state += 1;
-				current_state += 1;
-			}
-			//Operator '*': Implemented - Single and multiple * operators are working. Something funky happens with the more characters
-			// added into the regex, especially after a *. Not time to check it. Very close to getting this part fixed, most of it works
-			//Similar to + operator, remove previous transition to replace it with new one. 
-			// Step back 2 states for next and current to allow for proper transition. Push necessary states.
-			// Potential fix is similar to + operator with iterating over transitions instead of just checking index 0.
-			//At the end, add 2 to current state to get back, and set previous_char as *
-			else if c == '*' {
-
-				dfa.transitions.remove(dfa.transitions.len()-1);
-				let mut pushed_forward = false;
-				next_state -= 2;
-				current_state -= 2;
-
-				for a in dfa.alphabet.iter() {
-					if a == &previous_char {
-						next_state += 1;
-						states.push(next_state);
-					} else if *a == 'Σ' {
-						states.push(1);
-					} else {
-						if *a == transitions[0].chars {
-							states.push(transitions[0].state);
-						} else if !pushed_forward {
-							next_state += 1;
-							states.push(next_state);
-							pushed_forward = true;
-						} else {
-							states.push(1);
-						}
-					}
-				}
-
-				dfa.transitions.push(states);
-				current_state += 2;
-				previous_char = '*';
-			}
-			//All word character symbols: Implemented
-			//Allows for any character that is in the language to be added in, checks if there is a transition/cycle
-			//to be made, set the state as that before pushing. If it is not a transition, push to state 1
-			//if sigma symbol, push to state 1
-			else if c != 'Σ'
-			{
-				
-				for a in dfa.alphabet.iter() {
-					let mut was_transition = false;
-					if c == *a {
-						states.push(next_state);
-					}
-					else { 
-						for i in 0..transitions.len() {
-							if *a == transitions[i].chars {
-								states.push(transitions[i].state);
-								was_transition = true;
-							}
-						}
-
-						if was_transition == false {
-							if previous_char == '*' && *a != 'Σ' {
-								states.push(1);
-								previous_char = c;
-							} else {
-								states.push(1);
-							}
-						}
-					}
-				}
-
-				if previous_char != '|' {
-					dfa.transitions.push(states);
-				}
-
-				next_state += 1;
-				current_state += 1;
-
-				previous_char = c;
-			}
-		}
-
-		//Go back through and fix any transitions that weren't marked properly 
-		//    (i.e. | transitions to state 2 from state 4 if applicable)
-		for i in 0..dfa.transitions.len() {
-			for n in 0..dfa.transitions[i].len() {
-				if n < dfa.transitions[i].len() - 1 && dfa.transitions[i][n] == 1 {
-					for c in 0..transitions.len() {
-						if dfa.alphabet[n] == transitions[c].chars {
-							dfa.transitions[i][n] = transitions[c].state;
-						}
-					}
-				}
-			}
-		}
+/// Print the usage message and exit
+fn usage() -> ! {
+    eprintln!("Usage: cargo run -- [--repeat-limit N] [-i | --ignore-case] [--multiline] [--dot-all] [--search | --all | --set | --patterns FILE | --lex RULES [--graphviz] | --codegen FILE | --grep [-n] [-c] [-v] [-r] | --stream | --equiv | --simplify] [--engine dfa|lazy|derivative] [--construction thompson|glushkov] [--cache-size BYTES] 'regex'... [FILE...]");
+    std::process::exit(1);
+}
 
-		//Set final state as a cycle for transition matrix. If 3 states, push [3,3,3]
-		let mut final_state_count: Vec<usize> = Vec::new();
-		for _alphabet in dfa.alphabet.iter() {
-			final_state_count.push(final_state);
-		}
-		dfa.transitions.push(final_state_count);
+// *********************************************************************
+/// Return the RegEx passed as the last parameter along with any options
+/// given before it, or every pattern of a set, or the files to tokenize
+fn get_arguments(args: std::env::Args) -> Options {
+
+    // Get the arguments as a vector, skipping the program name
+    let args: Vec<String> = args.skip(1).collect();
+
+    let mut syntax = regex::Syntax::default();
+    let mut mode = Mode::Test;
+    let mut engine = Engine::Dfa;
+    let mut construction = Construction::Thompson;
+    let mut cache_size = 1 << 20;
+    let mut patterns: Vec<String> = Vec::new();
+    let mut positional: Vec<String> = Vec::new();
+    let mut rules = String::new();
+    let mut graphviz = false;
+    let mut output = String::new();
+    let mut grep = grep::Settings::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--repeat-limit" => {
+                i += 1;
+                syntax.repeat_limit = match args.get(i).and_then(|n| n.parse().ok()) {
+                    Some(n) => n,
+                    None => usage()
+                };
+            },
+            "--engine" => {
+                i += 1;
+                engine = match args.get(i).map(|e| e.as_str()) {
+                    Some("dfa") => Engine::Dfa,
+                    Some("lazy") => Engine::Lazy,
+                    Some("derivative") => Engine::Derivative,
+                    _ => usage()
+                };
+            },
+            "--construction" => {
+                i += 1;
+                construction = match args.get(i).map(|c| c.as_str()) {
+                    Some("thompson") => Construction::Thompson,
+                    Some("glushkov") => Construction::Glushkov,
+                    _ => usage()
+                };
+            },
+            "--cache-size" => {
+                i += 1;
+                cache_size = match args.get(i).and_then(|n| n.parse().ok()) {
+                    Some(n) => n,
+                    None => usage()
+                };
+            },
+            "-i" | "--ignore-case" => syntax.flags.case_insensitive = true,
+            "--multiline" => syntax.flags.multiline = true,
+            "--dot-all" => syntax.flags.dot_all = true,
+            "--search" => mode = Mode::Search,
+            "--all" => mode = Mode::SearchAll,
+            "--set" => mode = Mode::Set,
+            "--patterns" => {
+                i += 1;
+                let file = match args.get(i) {
+                    Some(f) => f,
+                    None => usage()
+                };
+                // One pattern per line, blank lines are skipped
+                patterns.extend(read_file(file).lines().filter(|l| !l.is_empty()).map(|l| l.to_string()));
+                mode = Mode::Set;
+            },
+            "--lex" => {
+                i += 1;
+                rules = match args.get(i) {
+                    Some(f) => f.to_string(),
+                    None => usage()
+                };
+                mode = Mode::Lex;
+            },
+            "--graphviz" => graphviz = true,
+            "--grep" => mode = Mode::Grep,
+            "--stream" => mode = Mode::Stream,
+            "--equiv" => mode = Mode::Equiv,
+            "--simplify" => mode = Mode::Simplify,
+            "-n" => grep.line_numbers = true,
+            "-c" => grep.count = true,
+            "-v" => grep.invert = true,
+            "-r" => grep.recursive = true,
+            "--codegen" => {
+                i += 1;
+                output = match args.get(i) {
+                    Some(f) => f.to_string(),
+                    None => usage()
+                };
+                mode = Mode::Codegen;
+            },
+            _ => positional.push(args[i].to_string())
+        }
+        i += 1;
+    }
 
+    // The lexer reads files, a set takes any number of patterns and
+    // everything else exactly one
+    let mut files: Vec<String> = Vec::new();
+    match mode {
+        Mode::Lex => files = positional,
+        Mode::Set => patterns.extend(positional),
+        Mode::Grep if !positional.is_empty() => {
+            files = positional.split_off(1);
+            patterns = positional;
+        },
+        _ => patterns = positional
+    }
+    let expected = match mode {
+        Mode::Set | Mode::Lex => None,
+        Mode::Equiv => Some(2),
+        _ => Some(1)
+    };
+    if (mode == Mode::Set && patterns.is_empty()) || expected.is_some_and(|n| patterns.len() != n) {
+        usage();
+    }
+    if graphviz && mode != Mode::Lex {
+        usage();
+    }
+    if (grep.line_numbers || grep.count || grep.invert || grep.recursive) && mode != Mode::Grep {
+        usage();
+    }
 
-		dfa
-	}
+    // Searching needs the DFA built by the subset construction, which is
+    // also the only engine that can start from a Glushkov automaton
+    if engine != Engine::Dfa && (mode != Mode::Test || construction != Construction::Thompson) {
+        usage();
+    }
 
+    // A set, lexer, stream, comparison or rewrite is always built with
+    // Thompson's construction
+    if matches!(mode, Mode::Set | Mode::Lex | Mode::Stream | Mode::Equiv | Mode::Simplify) && construction != Construction::Thompson {
+        usage();
+    }
 
+    Options{patterns, rules, files, graphviz, output, grep, syntax, mode, engine, construction, cache_size}
 }
 
 // *********************************************************************
@@ -340,18 +576,19 @@ impl DFA {
 impl StateGraph<> {
 
 	/// Create a state graph from a DFA structure
-    fn new_from_dfa(dfa: &DFA) -> Box<StateGraph> {
+    fn new_from_dfa(dfa: &dfa::DFA) -> Box<StateGraph> {
 
         // Create an empty graph object
         let mut graph = Box::new(StateGraph{alphabet: dfa.alphabet.clone(), 
                                             start_state: dfa.start - 1,
+                                            start_inside: dfa.start_inside - 1,
                                             states: vec!() });
 
         // Look through the transition table building state objects
         for row in dfa.transitions.iter() {
-            let mut v = Box::new(State{accept_state: false, transitions: vec!()});
+            let mut v = State{accept_state: false, accept_inside: false, transitions: vec!(), live: false};
             for col in row {
-                v.transitions.push(col-1);
+                v.transitions.push(vec![col-1]);
             } 
             graph.states.push(v);
         }    
@@ -359,41 +596,77 @@ impl StateGraph<> {
         // Set the accept states
         for astate in dfa.accept.iter() {
             graph.states[*astate - 1].accept_state = true;
+            graph.states[*astate - 1].live = true;
+        }
+        for astate in dfa.accept_inside.iter() {
+            graph.states[*astate - 1].accept_inside = true;
+            graph.states[*astate - 1].live = true;
         }
 
+        graph.mark_live();
         graph
 
     }
 
+	/// Create a state graph from a Glushkov automaton
+	///
+	/// State 0 is the start state and state p is entered by reading
+	/// position p, so a state may have no transition or several on a class.
+    fn new_from_glushkov(glushkov: &glushkov::Glushkov) -> Box<StateGraph> {
+
+        let mut graph = Box::new(StateGraph{alphabet: charclass::partition(&glushkov.classes()),
+                                            start_state: 0,
+                                            start_inside: 0,
+                                            states: vec!() });
+
+        for (follow, accept) in glushkov.follow.iter().zip(glushkov.accept.iter()) {
+            let mut v = State{accept_state: *accept, accept_inside: *accept, transitions: vec!(), live: *accept};
+            for class in graph.alphabet.iter() {
+                let c = class.first().unwrap();
+                v.transitions.push(follow.iter()
+                                         .filter(|p| glushkov.classes[**p].as_ref().is_some_and(|pc| pc.contains(c)))
+                                         .cloned()
+                                         .collect());
+            }
+            graph.states.push(v);
+        }
+
+        graph.mark_live();
+        graph
+
+    }
+
+    /// A state is live when one of its transitions leads to a live state,
+    /// repeat until nothing changes
+    fn mark_live(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for n in 0..self.states.len() {
+                if !self.states[n].live
+                   && self.states[n].transitions.iter().flatten().any(|t| self.states[*t].live) {
+                    self.states[n].live = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+
     /// Execute the graph on a sentence
-    /// Return Err if a character not in the alphabet is encountered
-    /// Return Ok and a bool indicating accept (true) or reject (false)
-    fn test_sentence(&self, sentence: &str) -> Result<bool, String> {
+    /// Return a bool indicating accept (true) or reject (false)
+    fn test_sentence(&self, sentence: &str) -> bool {
 
         let mut state = self.start_state;
 
-        //Full alphabet to test against for sigma character
-        let full_alphabet: Vec<char> = "abcdefghijklmnopqrstuvwxyz0123456789 ".chars().collect();
-
         for ch in sentence.chars() {
 
-        	//Check if character is a word character. Accept it if it is and change it to the 'Σ' symbol for matching purposes
-    		let mut c = ch;
-    		if !self.alphabet.contains(&c) && full_alphabet.contains(&c) {
-    			c = 'Σ';
-    		}
-            let state_no = match self.alphabet.iter().position(|v| *v == ch || *v == c) {
-                Some(t) => t,
-                None => return Err(format!("Character <{}> does not have a transition", ch))
-            };
-
             print!("δ(q{}, {}) → ", state+1, ch);
-            state = self.states[state].transitions[state_no];
+            state = self.next_state(state, ch);
             println!("(q{})", state+1);
 
         }
 
-        Ok(self.states[state].accept_state)
+        self.states[state].accept_state
     }
 
 
@@ -413,8 +686,11 @@ impl StateGraph<> {
         println!("\tstart -> q{}", self.start_state+1);
         for (n, state) in self.states.iter().enumerate() {
 
-            for (i, ch) in self.alphabet.iter().enumerate() {
-                println!("\tq{} -> q{} [label=\"{}\"]", n+1, state.transitions[i] + 1, ch);
+            for (i, class) in self.alphabet.iter().enumerate() {
+                let label = class.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+                for target in state.transitions[i].iter() {
+                    println!("\tq{} -> q{} [label=\"{}\"]", n+1, target + 1, label);
+                }
             }
 
         }
@@ -422,7 +698,150 @@ impl StateGraph<> {
 
     }
 
-    fn process(&self) {
+    /// The state reached from `state` on `ch`
+    ///
+    /// The last class of the alphabet holds every character the regex does
+    /// not mention, so some class always holds `ch`.
+    fn next_state(&self, state: usize, ch: char) -> usize {
+        let col = charclass::column(&self.alphabet, ch);
+        self.states[state].transitions[col][0]
+    }
+
+    /// Can a match end in `state` at byte offset `end` of the text
+    fn accepts_at(&self, state: usize, text: &str, end: usize) -> bool {
+        if end == text.len() {
+            self.states[state].accept_state
+        } else {
+            self.states[state].accept_inside
+        }
+    }
+
+    /// The longest match starting exactly at byte offset `start`
+    ///
+    /// The text is a single line, so `^` matches only at its start and `$`
+    /// only at its end.
+    fn longest_at(&self, text: &str, start: usize) -> Option<usize> {
+
+        let mut state = if start == 0 { self.start_state } else { self.start_inside };
+        let mut end = if self.accepts_at(state, text, start) { Some(start) } else { None };
+
+        for (i, ch) in text[start..].char_indices() {
+            state = self.next_state(state, ch);
+            if !self.states[state].live {
+                break;
+            }
+            let pos = start + i + ch.len_utf8();
+            if self.accepts_at(state, text, pos) {
+                end = Some(pos);
+            }
+        }
+
+        end
+    }
+
+    /// The leftmost-longest match starting at or after byte offset `from`
+    ///
+    /// Returns the byte range of the match within the text.
+    fn find_at(&self, text: &str, from: usize) -> Option<Range<usize>> {
+
+        let starts = text[from..].char_indices()
+                                 .map(|(i, _)| from + i)
+                                 .chain(std::iter::once(text.len()));
+
+        for start in starts {
+            if let Some(end) = self.longest_at(text, start) {
+                return Some(start..end);
+            }
+        }
+
+        None
+    }
+
+    /// The leftmost-longest match in the text
+    fn find(&self, text: &str) -> Option<Range<usize>> {
+        self.find_at(text, 0)
+    }
+
+    /// Every non-overlapping leftmost-longest match, scanning left to right
+    ///
+    /// An empty match directly after the previous match is skipped.
+    fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+
+        let mut matches: Vec<Range<usize>> = Vec::new();
+        let mut from = 0;
+
+        while from <= text.len() {
+
+            let m = match self.find_at(text, from) {
+                Some(m) => m,
+                None => break
+            };
+
+            // Step past one character after an empty match so the scan moves on
+            let next = if m.is_empty() {
+                m.end + text[m.end..].chars().next().map_or(1, |c| c.len_utf8())
+            } else {
+                m.end
+            };
+
+            let touches_previous = matches.last().is_some_and(|p: &Range<usize>| p.end == m.start);
+            if !(m.is_empty() && touches_previous) {
+                matches.push(m);
+            }
+
+            from = next;
+        }
+
+        matches
+    }
+
+    /// Print the lines of stdin that contain a match, highlighting the
+    /// leftmost-longest match, or every match when `all` is set
+    ///
+    /// Matches are shown in bold red on a terminal and in square brackets
+    /// otherwise.
+    fn search_lines(&self, all: bool) {
+
+        let (open, close) = if io::stdout().is_terminal() {
+            ("\x1b[1;31m", "\x1b[0m")
+        } else {
+            ("[", "]")
+        };
+
+        let stdin = io::stdin();
+        for (n, line) in stdin.lock().lines().enumerate() {
+
+            // Get the line out of the Result, should never error
+            let sentence = &line.unwrap();
+
+            let matches = if all {
+                self.find_all(sentence)
+            } else {
+                self.find(sentence).into_iter().collect()
+            };
+
+            if matches.is_empty() {
+                continue;
+            }
+
+            let mut highlighted = String::new();
+            let mut last = 0;
+            for m in matches.iter() {
+                highlighted.push_str(&sentence[last..m.start]);
+                highlighted.push_str(open);
+                highlighted.push_str(&sentence[m.clone()]);
+                highlighted.push_str(close);
+                last = m.end;
+            }
+            highlighted.push_str(&sentence[last..]);
+
+            println!("{}: {}", n + 1, highlighted);
+        }
+    }
+
+    /// Accept or reject each line of stdin, printing what every capture
+    /// group matched for the accepted ones
+    fn process(&self, vm: &pikevm::PikeVM) {
     	let stdin = io::stdin();
 	    for line in stdin.lock().lines() {
 
@@ -430,10 +849,19 @@ impl StateGraph<> {
 	        let sentence = &line.unwrap();
 	        println!("Processing sentence <{}>", sentence);
 
-	        match self.test_sentence(sentence) {
-	            Ok(b) => println!("{}", 
-	                              if b {"Accept"} else {"Reject"}),
-	            Err(s) => println!("Error processing sentence: {}", s)
+	        println!("{}", if self.test_sentence(sentence) {"Accept"} else {"Reject"});
+
+	        //The DFA decided acceptance, the Pike VM recovers the groups
+	        if vm.groups() > 0 {
+	            if let Some(groups) = vm.captures(sentence) {
+	                for (n, group) in groups.iter().enumerate().skip(1) {
+	                    match group {
+	                        Some(span) => println!("Group {}: <{}> at {}..{}",
+	                                               n, &sentence[span.clone()], span.start, span.end),
+	                        None => println!("Group {}: no match", n)
+	                    }
+	                }
+	            }
 	        }
 
     	}
@@ -446,15 +874,823 @@ impl StateGraph<> {
 mod test {
 
     use super::*;
-    
+
+    //Parse a regex with the default settings
+    fn parse(pattern: &str) -> Result<regex::Regex, regex::RegexError> {
+        regex::parse(pattern, &regex::Syntax::default())
+    }
+
+    //Build the state graph for a regex parsed with the default settings
+    fn graph_for(pattern: &str) -> Box<StateGraph> {
+        let nfa = nfa::NFA::new_from_regex(&parse(pattern).expect("Regex Failure:"));
+        StateGraph::new_from_dfa(&dfa::DFA::new_from_nfa(&nfa))
+    }
+
+    //Build the Pike VM for a regex parsed with the default settings
+    fn vm_for(pattern: &str) -> pikevm::PikeVM {
+        pikevm::PikeVM::new(nfa::NFA::new_from_regex(&parse(pattern).expect("Regex Failure:")))
+    }
+
+    //Numbers below the bound given, from a xorshift generator so random tests repeat
+    fn xorshift(mut seed: u64) -> impl FnMut(usize) -> usize {
+        move |bound| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % bound as u64) as usize
+        }
+    }
+
+    //Every string over the symbols up to the given length, shortest first
+    fn strings_up_to(symbols: &[char], length: usize) -> Vec<String> {
+        let mut strings: Vec<String> = vec![String::new()];
+        let mut last = strings.clone();
+        for _ in 0..length {
+            last = last.iter().flat_map(|s| symbols.iter().map(move |c| format!("{}{}", s, c))).collect();
+            strings.extend(last.iter().cloned());
+        }
+        strings
+    }
+
     //This test is used to make sure that it creates a graphviz file
     #[test]
     fn test1() {
-        let dfa = DFA::new_from_regex("a*b");
-
 		//Create the dfa structure based on in RegEx entered from the command line
-	    let state_graph = StateGraph::new_from_dfa(&dfa);
+	    let state_graph = graph_for("a*b");
 
 	    state_graph.write_graphviz();
     }
-}
\ No newline at end of file
+
+    //This test is used to make sure that combinations of |, * and + give the right answers
+    #[test]
+    fn test2() {
+        let state_graph = graph_for("ab*|c+a");
+
+        for sentence in ["a", "ab", "abbb", "ca", "ccca"].iter() {
+            assert!(state_graph.test_sentence(sentence));
+        }
+        for sentence in ["", "ac", "c", "abc", "cab", "bbb"].iter() {
+            assert!(!state_graph.test_sentence(sentence));
+        }
+    }
+
+    //This test is used to make sure that an operator with nothing to repeat is an error
+    #[test]
+    fn test3() {
+        assert!(parse("*a").is_err());
+        assert!(parse("a|+b").is_err());
+    }
+
+    //This test is used to make sure that the parser builds the expected syntax tree
+    #[test]
+    fn test4() {
+        use regex::Regex::*;
+
+        assert_eq!(parse("ab|c*"),
+                   Ok(Alternation(vec![Concat(vec![Literal('a'), Literal('b')]),
+                                       Star(Box::new(Literal('c')))])));
+        assert_eq!(parse("a?+"),
+                   Ok(Plus(Box::new(Optional(Box::new(Literal('a')))))));
+    }
+
+    //This test is used to make sure that syntax errors point at the right place
+    #[test]
+    fn test5() {
+        assert_eq!(parse("*a").unwrap_err().offset, 0);
+        assert_eq!(parse("a||").unwrap_err().offset, 2);
+        assert_eq!(parse("ab|").unwrap_err().offset, 3);
+        assert_eq!(parse("").unwrap_err().offset, 0);
+        assert_eq!(parse("x(ab").unwrap_err().offset, 1);
+        assert_eq!(parse("ab)").unwrap_err().offset, 2);
+
+        let error = parse("Σa|*").unwrap_err();
+        assert_eq!(error.offset, 4);
+        assert_eq!(error.to_string(),
+                   "Regex error at offset 4: Operator * has nothing to repeat\n    Σa|*\n       ^");
+    }
+
+    //This test is used to make sure that nested groups are built with the right precedence
+    #[test]
+    fn test6() {
+        let cases = [("(ab)*c", vec!["c", "abc", "ababc"], vec!["", "ac", "abac", "ab"]),
+                     ("a(b|c)d", vec!["abd", "acd"], vec!["ad", "abcd", "ab"]),
+                     ("((a|b)c)+", vec!["ac", "bc", "acbcac"], vec!["", "ab", "acb"]),
+                     ("ab*|cd", vec!["a", "abb", "cd"], vec!["abd", "c", "abcd"]),
+                     ("a(b(c|d)*)?e", vec!["ae", "abe", "abcdce"], vec!["ace", "abdb"])];
+
+        for (pattern, accepted, rejected) in cases.iter() {
+            let state_graph = graph_for(pattern);
+
+            for sentence in accepted.iter() {
+                assert!(state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+            for sentence in rejected.iter() {
+                assert!(!state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+        }
+    }
+
+    //This test is used to make sure that empty groups match only the empty string
+    #[test]
+    fn test7() {
+        let state_graph = graph_for("a()b|()*");
+
+        assert!(state_graph.test_sentence("ab"));
+        assert!(state_graph.test_sentence(""));
+        assert!(!state_graph.test_sentence("a"));
+
+        assert_eq!(parse("(|a)b").unwrap_err().offset, 1);
+    }
+
+    //This test is used to make sure that shorthand, bracket and negated classes match the right characters
+    #[test]
+    fn test8() {
+        let cases = [(r"\d+-\w", vec!["2020-x", "7-_", "12-Q"], vec!["-x", "12-", "a-b"]),
+                     ("[A-Z][a-z]*", vec!["Paige", "Q"], vec!["paige", "PaigE", ""]),
+                     ("[^a-c]+", vec!["Dog!", "?", "xyz"], vec!["", "dab"]),
+                     (r"a\sb\S", vec!["a b!", "a\tbb"], vec!["ab b", "a b "]),
+                     ("[]a-]x", vec!["]x", "ax", "-x"], vec!["bx", "x"]),
+                     (r"[\d_.]+\W", vec!["3.1_4!", "._ "], vec!["3.1", "a!"])];
+
+        for (pattern, accepted, rejected) in cases.iter() {
+            let state_graph = graph_for(pattern);
+
+            for sentence in accepted.iter() {
+                assert!(state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+            for sentence in rejected.iter() {
+                assert!(!state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+        }
+    }
+
+    //This test is used to make sure that the alphabet is split into disjoint classes
+    #[test]
+    fn test9() {
+        use charclass::CharClass;
+
+        let word = CharClass::word();
+        let hex = CharClass::new(vec![('a', 'f'), ('0', '9')]);
+        let alphabet = charclass::partition(&[word.clone(), hex.clone()]);
+
+        assert_eq!(alphabet.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+                   vec!["[0-9a-f]", "[A-Z_g-z]", "[^0-9A-Z_a-z]"]);
+        assert_eq!(CharClass::new(vec![('a', 'c'), ('d', 'd'), ('x', 'x')]).to_string(), "[a-dx]");
+        assert!(word.negate().contains('!') && !word.negate().contains('q'));
+        assert_eq!(word.negate().negate(), word);
+
+        assert_eq!(parse("x[ab").unwrap_err().offset, 1);
+        assert_eq!(parse("[z-a]").unwrap_err().offset, 3);
+        assert_eq!(parse(r"a\q").unwrap_err().offset, 1);
+    }
+
+    //This test is used to make sure that optional and counted repetitions match the right number of times
+    #[test]
+    fn test10() {
+        let cases = [("ab?c", vec!["ac", "abc"], vec!["abbc", "a"]),
+                     ("a{3}", vec!["aaa"], vec!["aa", "aaaa"]),
+                     ("(ab){2,}", vec!["abab", "ababab"], vec!["ab", "aba"]),
+                     ("x[0-9]{1,3}y", vec!["x1y", "x12y", "x123y"], vec!["xy", "x1234y"]),
+                     ("a{0}b{0,1}", vec!["", "b"], vec!["a", "bb"])];
+
+        for (pattern, accepted, rejected) in cases.iter() {
+            let state_graph = graph_for(pattern);
+
+            for sentence in accepted.iter() {
+                assert!(state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+            for sentence in rejected.iter() {
+                assert!(!state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+        }
+    }
+
+    //This test is used to make sure that malformed and oversized counts are errors
+    #[test]
+    fn test11() {
+        assert_eq!(parse("a{3,1}").unwrap_err().offset, 1);
+        assert_eq!(parse("a{x}").unwrap_err().offset, 2);
+        assert_eq!(parse("a{2").unwrap_err().offset, 3);
+        assert_eq!(parse("{2}").unwrap_err().offset, 0);
+        assert_eq!(parse("a{99999999999999999999999}").unwrap_err().offset, 2);
+
+        assert_eq!(parse("ab{1000}").unwrap_err().offset, 2);
+        assert_eq!(parse("(a{30}){30}").unwrap_err().offset, 7);
+        assert_eq!(parse("(a{500}){500}").unwrap_err().offset, 8);
+        assert_eq!(parse("(a{5}){5}").unwrap().size(), 25);
+        assert_eq!(parse("a{300}b{300}").unwrap_err().offset, 0);
+        assert!(parse(&"a".repeat(600)).is_ok());
+
+        let syntax = regex::Syntax{repeat_limit: 1000, ..Default::default()};
+        assert!(regex::parse("ab{1000}", &syntax).is_ok());
+    }
+
+    //This test is used to make sure that escaped operators and Σ are matched literally
+    #[test]
+    fn test12() {
+        let cases = [(r"a\*b", vec!["a*b"], vec!["ab", "aab"]),
+                     (r"\(\)\|\\\+", vec!["()|\\+"], vec!["", "()"]),
+                     (r"[\]\-\\]+", vec!["]-\\", "--"], vec!["a", "[]"]),
+                     (r"\{\d\}\.\?", vec!["{7}.?"], vec!["{7}", "77.?"]),
+                     ("Σ+|a", vec!["Σ", "ΣΣ", "a"], vec!["b", "aΣ"]),
+                     (r"\u{3A3}\t[\u{41}-\u{43}]", vec!["Σ\tB"], vec!["Σ\tD", "Σ B"])];
+
+        for (pattern, accepted, rejected) in cases.iter() {
+            let state_graph = graph_for(pattern);
+
+            for sentence in accepted.iter() {
+                assert!(state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+            for sentence in rejected.iter() {
+                assert!(!state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+        }
+
+        assert_eq!(parse(r"ab\").unwrap_err().offset, 2);
+        assert_eq!(parse(r"\k").unwrap_err().offset, 0);
+        assert_eq!(parse(r"x\u{D800}").unwrap_err().offset, 1);
+        assert_eq!(parse(r"[a-\d]").unwrap_err().offset, 3);
+    }
+
+    //This test is used to make sure that searching finds leftmost-longest spans
+    #[test]
+    fn test13() {
+        let state_graph = graph_for("ab+|b");
+
+        assert_eq!(state_graph.find("xxabbbyab"), Some(2..6));
+        assert_eq!(state_graph.find_all("xxabbbyab"), vec![2..6, 7..9]);
+        assert_eq!(state_graph.find_all("bab!b"), vec![0..1, 1..3, 4..5]);
+        assert_eq!(state_graph.find("xyz"), None);
+        assert_eq!(state_graph.find("ΣΣab"), Some(4..6));
+    }
+
+    //This test is used to make sure that empty matches do not stall or overlap the search
+    #[test]
+    fn test14() {
+        let state_graph = graph_for("a*");
+
+        assert_eq!(state_graph.find("baa"), Some(0..0));
+        assert_eq!(state_graph.find_all("baab"), vec![0..0, 1..3, 4..4]);
+        assert_eq!(state_graph.find_all(""), vec![0..0]);
+    }
+
+    //This test is used to make sure that the Pike VM reports what each group matched
+    #[test]
+    fn test15() {
+        let vm = vm_for("(a+)(b*)");
+        assert_eq!(vm.groups(), 2);
+        assert_eq!(vm.captures("aab"), Some(vec![Some(0..3), Some(0..2), Some(2..3)]));
+        assert_eq!(vm.captures("ba"), None);
+
+        //The left side of | is preferred when both sides can match
+        let vm = vm_for("(a|ab)(c|bcd)");
+        assert_eq!(vm.captures("abcd"), Some(vec![Some(0..4), Some(0..1), Some(1..4)]));
+
+        //A repeated group reports its last iteration, a skipped one reports nothing
+        let vm = vm_for("(\\w)*x(y)?z");
+        assert_eq!(vm.captures("abcxz"), Some(vec![Some(0..5), Some(2..3), None]));
+        assert_eq!(vm.captures("xyz"), Some(vec![Some(0..3), None, Some(1..2)]));
+    }
+
+    //This test is used to make sure that groups are numbered by their opening parenthesis
+    #[test]
+    fn test16() {
+        let vm = vm_for("((Σ)(b(c)))()");
+        assert_eq!(vm.groups(), 5);
+        assert_eq!(vm.captures("Σbc"),
+                   Some(vec![Some(0..4), Some(0..4), Some(0..2), Some(2..4), Some(3..4), Some(4..4)]));
+
+        //Greedy repetition takes as much as it can while still matching the whole text
+        let vm = vm_for("(a*)(a{2})");
+        assert_eq!(vm.captures("aaaa"), Some(vec![Some(0..4), Some(0..2), Some(2..4)]));
+    }
+
+    //This test is used to make sure that the lazy DFA agrees with the full DFA even when its cache thrashes
+    #[test]
+    fn test17() {
+        let pattern = "(a|b)*a(a|b){3}";
+        let state_graph = graph_for(pattern);
+        let mut lazy = lazy::LazyDFA::new(nfa::NFA::new_from_regex(&parse(pattern).unwrap()), 600);
+
+        //Every string of a and b up to length 8
+        for length in 0..9 {
+            for bits in 0..(1 << length) {
+                let sentence: String = (0..length).map(|i| if bits & (1 << i) != 0 {'a'} else {'b'}).collect();
+                assert_eq!(lazy.is_match(&sentence), state_graph.test_sentence(&sentence), "{}", sentence);
+            }
+        }
+
+        let stats = lazy.stats();
+        assert!(stats.hits > 0 && stats.cache_clears > 0 && stats.fallbacks > 0);
+    }
+
+    //This test is used to make sure that the lazy DFA handles a pattern whose full DFA is huge
+    #[test]
+    fn test18() {
+        let pattern = "(a|b)*a(a|b){20}";
+        let mut lazy = lazy::LazyDFA::new(nfa::NFA::new_from_regex(&parse(pattern).unwrap()), 1 << 20);
+
+        let accepted = format!("{}a{}", "ab".repeat(50), "b".repeat(20));
+        let rejected = format!("{}b{}", "ab".repeat(50), "b".repeat(20));
+        assert!(lazy.is_match(&accepted));
+        assert!(!lazy.is_match(&rejected));
+        assert!(!lazy.is_match("c"));
+
+        //The second run of the same text is answered from the cache
+        let built = lazy.stats().states_built;
+        assert!(lazy.is_match(&accepted));
+        assert_eq!(lazy.stats().states_built, built);
+        assert_eq!(lazy.stats().fallbacks, 0);
+    }
+
+    //This test is used to make sure that the derivative engine agrees with the subset construction DFA
+    #[test]
+    fn test19() {
+        let patterns = ["(ab|a)*b?", "a(b|c)*c", "(a|b)*a(a|b){2}", "[^b]+b{1,2}|()", "(a*b*)*c"];
+
+        for pattern in patterns.iter() {
+            let regex = parse(pattern).unwrap();
+            let expr = derivative::Expr::new_from_regex(&regex);
+            let state_graph = graph_for(pattern);
+            let derivative_graph = StateGraph::new_from_dfa(&dfa::DFA::new_from_derivatives(&expr));
+
+            for sentence in strings_up_to(&['a', 'b', 'c'], 6) {
+                let expected = state_graph.test_sentence(&sentence);
+                assert_eq!(expr.test_sentence(&sentence), expected, "{} {}", pattern, sentence);
+                assert_eq!(derivative_graph.test_sentence(&sentence), expected,
+                           "{} {}", pattern, sentence);
+            }
+        }
+    }
+
+    //This test is used to make sure that the smart constructors simplify derivatives
+    #[test]
+    fn test20() {
+        use derivative::Expr;
+
+        let expr = Expr::new_from_regex(&parse("(a|b)*abb").unwrap());
+        assert_eq!(expr.derivative('c'), Expr::Empty);
+        assert_eq!(expr.derivative('b').to_string(), "[a-b]*abb");
+        assert_eq!(expr.derivative('a').to_string(), "bb|[a-b]*abb");
+
+        //The derivative DFA for (a|b)*abb has the four states of the textbook machine plus
+        // the dead state other characters lead to
+        let dfa = dfa::DFA::new_from_derivatives(&expr);
+        assert_eq!(dfa.transitions.len(), 5);
+        assert_eq!(Expr::star(Expr::star(Expr::Epsilon)), Expr::Epsilon);
+    }
+
+    //This test is used to make sure that the Glushkov automaton has one state per symbol and
+    // accepts the same sentences as Thompson's construction
+    #[test]
+    fn test21() {
+        let glushkov = glushkov::Glushkov::new_from_regex(&parse("(a|b)*abb").unwrap());
+        assert_eq!(glushkov.classes.len(), 6);
+        assert_eq!(glushkov.follow[0], vec![1, 2, 3]);
+        assert_eq!(glushkov.follow[3], vec![4]);
+        assert_eq!(glushkov.accept, vec![false, false, false, false, false, true]);
+
+        //The drawn graph keeps the nondeterminism, both a positions follow the start state
+        let drawn = StateGraph::new_from_glushkov(&glushkov);
+        assert_eq!(drawn.states.len(), 6);
+        assert_eq!(drawn.states[0].transitions[0], vec![1, 3]);
+
+        let patterns = ["(ab|a)*b?", "a(b|c)*c", "(a|b)*a(a|b){2}", "[^b]+b{1,2}|()", "(a*b*)*c", "(a{2,}|b?){1,2}"];
+
+        for pattern in patterns.iter() {
+            let regex = parse(pattern).unwrap();
+            let state_graph = graph_for(pattern);
+            let glushkov_graph = StateGraph::new_from_dfa(
+                &dfa::DFA::new_from_glushkov(&glushkov::Glushkov::new_from_regex(&regex)));
+
+            for sentence in strings_up_to(&['a', 'b', 'c'], 6) {
+                assert_eq!(glushkov_graph.test_sentence(&sentence),
+                           state_graph.test_sentence(&sentence),
+                           "{} {}", pattern, sentence);
+            }
+        }
+    }
+
+    //This test is used to make sure that dot and the other class make the DFA total
+    #[test]
+    fn test22() {
+        let cases = [("a.c", vec!["abc", "aΣc", "a.c", "aZc"], vec!["ac", "a\ncc", "abcd"]),
+                     ("[^a]b|.", vec!["Xb", "!", "ü"], vec!["ab", "", "\n"]),
+                     (r"\.+", vec![".", "..."], vec!["a", ".a"]),
+                     ("ab", vec!["ab"], vec!["AB", "a!", "a b", "日本"])];
+
+        for (pattern, accepted, rejected) in cases.iter() {
+            let state_graph = graph_for(pattern);
+            for sentence in accepted {
+                assert!(state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+            for sentence in rejected {
+                assert!(!state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+
+            //Every state has a transition on every class, the last one being other
+            for state in state_graph.states.iter() {
+                assert_eq!(state.transitions.len(), state_graph.alphabet.len());
+            }
+        }
+
+        let state_graph = graph_for("ab");
+        assert_eq!(state_graph.alphabet.last().unwrap().to_string(), "[^a-b]");
+        assert_eq!(graph_for("()").alphabet.len(), 1);
+    }
+
+    //This test is used to make sure that inline flags and case folding are applied while parsing
+    #[test]
+    fn test23() {
+        let cases = [("(?i)abc", vec!["abc", "ABC", "aBc"], vec!["abd", "ab"]),
+                     ("a(?i)b|c", vec!["ab", "aB", "C"], vec!["Ab", "AB"]),
+                     ("(a(?i)b)c", vec!["aBc"], vec!["aBC", "ABc"]),
+                     ("(?i)a(?-i)b", vec!["Ab"], vec!["AB"]),
+                     ("(?i)[^k]x", vec!["ax", "AX"], vec!["kx", "KX", "\u{212A}x"]),
+                     ("(?i)k", vec!["k", "K", "\u{212A}"], vec!["x"]),
+                     ("(?i)σ+", vec!["Σσς"], vec!["s"]),
+                     ("(?i)straße", vec!["STRAẞE", "Straße"], vec!["STRASSE"]),
+                     ("(?s)a.b", vec!["a\nb", "axb"], vec!["ab"]),
+                     ("a.b", vec!["axb"], vec!["a\nb"])];
+
+        for (pattern, accepted, rejected) in cases.iter() {
+            let state_graph = graph_for(pattern);
+            for sentence in accepted {
+                assert!(state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+            for sentence in rejected {
+                assert!(!state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+            }
+        }
+
+        //Flags given on the command line apply from the start of the pattern
+        let syntax = regex::Syntax{flags: regex::Flags{case_insensitive: true, ..Default::default()},
+                                   ..Default::default()};
+        assert_eq!(regex::parse("a", &syntax).unwrap(), parse("[aA]").unwrap());
+        assert_eq!(parse("(?x)a").unwrap_err().offset, 2);
+        assert_eq!(parse("(?i").unwrap_err().offset, 0);
+    }
+
+    //This test is used to make sure that ^ and $ match at the text or line boundaries
+    #[test]
+    fn test24() {
+        let cases = [("^a$", vec!["a"], vec!["aa"]),
+                     ("a^b", vec![], vec!["ab"]),
+                     ("a$\n^b", vec![], vec!["a\nb"]),
+                     ("(?m)a$\n^b", vec!["a\nb"], vec!["a\n\nb"]),
+                     (r"(?m)(^\w*$\n?)*", vec!["ab\ncd\n", ""], vec!["a b"]),
+                     ("(^|x)a$|b", vec!["a", "xa", "b"], vec!["xxa"]),
+                     ("$^", vec![""], vec!["a"]),
+                     ("(?m)\n$^\n", vec!["\n\n"], vec!["\n\n\n"])];
+
+        for (pattern, accepted, rejected) in cases.iter() {
+            let state_graph = graph_for(pattern);
+            let vm = vm_for(pattern);
+            let mut lazy = lazy::LazyDFA::new(nfa::NFA::new_from_regex(&parse(pattern).unwrap()), 1 << 16);
+            for sentence in accepted {
+                assert!(state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+                assert!(vm.captures(sentence).is_some(), "{} {}", pattern, sentence);
+                assert!(lazy.is_match(sentence), "{} {}", pattern, sentence);
+            }
+            for sentence in rejected {
+                assert!(!state_graph.test_sentence(sentence), "{} {}", pattern, sentence);
+                assert!(vm.captures(sentence).is_none(), "{} {}", pattern, sentence);
+                assert!(!lazy.is_match(sentence), "{} {}", pattern, sentence);
+            }
+        }
+
+        //Searching honours the anchors within the line
+        assert_eq!(graph_for("^a").find_all("aaa"), vec![0..1]);
+        assert_eq!(graph_for("a$").find_all("aaa"), vec![2..3]);
+        assert_eq!(graph_for("b|^a+$").find("aab"), Some(2..3));
+        assert_eq!(graph_for("^a+$").find("aaa"), Some(0..3));
+    }
+
+    //This test is used to make sure that a regex set reports the same patterns as matching one at a time
+    #[test]
+    fn test25() {
+        let patterns: Vec<String> = ["a*", "(a|b)*b", "[ab]{2}", "^b.$", "c|()", "(ab|a)*b?"]
+                                        .iter().map(|p| p.to_string()).collect();
+        let set = regexset::RegexSet::new(&patterns, &regex::Syntax::default()).unwrap();
+        let graphs: Vec<Box<StateGraph>> = patterns.iter().map(|p| graph_for(p)).collect();
+
+        for sentence in strings_up_to(&['a', 'b', 'c'], 5) {
+            let expected: Vec<usize> = (0..patterns.len()).filter(|i| graphs[*i].test_sentence(&sentence))
+                                                          .collect();
+            assert_eq!(set.matches(&sentence), &expected[..], "{}", sentence);
+        }
+
+        assert_eq!(set.matches(""), &[0, 4, 5]);
+        assert_eq!(set.matches("ab"), &[1, 2, 5]);
+        assert_eq!(set.matches("bc"), &[3]);
+
+        //A malformed pattern is reported with its own text
+        let error = regexset::RegexSet::new(&["a".to_string(), "b|*".to_string()], &regex::Syntax::default());
+        assert_eq!(error.err().map(|e| (e.pattern, e.offset)), Some(("b|*".to_string(), 2)));
+
+        let empty = regexset::RegexSet::new(&[], &regex::Syntax::default()).unwrap();
+        assert!(empty.matches("").is_empty());
+    }
+
+    //This test is used to make sure that the lexer takes the longest match and breaks ties by rule order
+    #[test]
+    fn test26() {
+        let rules = lexer::parse_rules("# keywords come before identifiers\n\
+                                        IF if\n\
+                                        ID [a-z]\\w*\n\
+                                        \n\
+                                        NUM \\d+(\\.\\d+)?\n\
+                                        OP [-+*/=]|==\n\
+                                        WS \\s+\n").unwrap();
+        assert_eq!(rules[1], lexer::Rule{name: "ID".to_string(), pattern: "[a-z]\\w*".to_string()});
+        let lexer = lexer::Lexer::new(&rules, &regex::Syntax::default()).unwrap();
+
+        let tokens: Vec<String> = lexer.tokenize("if iffy==3.25\n x @+1.")
+                                       .iter()
+                                       .map(|t| match t {
+                                           Ok(token) => token.to_string(),
+                                           Err(e) => e.to_string()
+                                       })
+                                       .collect();
+        assert_eq!(tokens, vec!["1:1 IF <if>", "1:3 WS < >", "1:4 ID <iffy>", "1:8 OP <==>",
+                                "1:10 NUM <3.25>", "1:14 WS <\\n >", "2:2 ID <x>", "2:3 WS < >",
+                                "Lexical error at 2:4: no rule matches <@>", "2:5 OP <+>", "2:6 NUM <1>",
+                                "Lexical error at 2:7: no rule matches <.>"]);
+
+        //Accept states are labelled with the token of the first rule accepting there
+        let dot = lexer.graphviz();
+        assert!(dot.contains("\\nIF\"]"));
+        assert!(dot.contains("\\nID\"]"));
+        assert_eq!(lexer::parse_rules("A a\nB\n"), Err("Rule on line 2 has no regex".to_string()));
+        assert!(lexer::Lexer::new(&lexer::parse_rules("A ^a").unwrap(), &regex::Syntax::default()).is_err());
+
+        //Backslashes and quotes in a token name are escaped in its label
+        let quoted = lexer::Lexer::new(&[lexer::Rule{name: "Q\\\"".to_string(), pattern: "q".to_string()}],
+                                       &regex::Syntax::default()).unwrap();
+        assert!(quoted.graphviz().contains("\\nQ\\\\\\\"\"]"), "{}", quoted.graphviz());
+    }
+
+    //This test is used to make sure that the generated Rust code accepts the same strings as the DFA
+    #[test]
+    fn test27() {
+        let patterns = ["(a|b)*abb", "[^a-c]+\\d?|()", "(?i)σ.[x-zΣ]", "a{2,3}$"];
+
+        for (n, pattern) in patterns.iter().enumerate() {
+            let regex = parse(pattern).unwrap();
+            let dfa = dfa::DFA::new_from_nfa(&nfa::NFA::new_from_regex(&regex));
+            let state_graph = StateGraph::new_from_dfa(&dfa);
+
+            //Compile the generated module with a main printing whether each line of stdin is accepted
+            let dir = std::env::temp_dir().join(format!("project1-codegen-{}-{}", n, std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let program = format!("{}\nfn main() {{\n    use std::io::BufRead;\n    \
+                                   for line in std::io::stdin().lock().lines() {{\n        \
+                                   println!(\"{{}}\", accepts(&line.unwrap()));\n    }}\n}}\n",
+                                  codegen::rust_source(&dfa, pattern));
+            std::fs::write(dir.join("main.rs"), program).unwrap();
+            let status = std::process::Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+                            .arg("--edition=2018")
+                            .arg("-o").arg(dir.join("generated"))
+                            .arg(dir.join("main.rs"))
+                            .status()
+                            .expect("Unable to run rustc");
+            assert!(status.success(), "generated code for {} does not compile", pattern);
+
+            //Random strings from a xorshift generator, including characters no class mentions
+            let symbols = ['a', 'b', 'c', '7', 'x', 'Σ', 'σ', 'ς', 'Q', '\t', '日'];
+            let mut next = xorshift(88172645);
+            let inputs: Vec<String> = (0..400).map(|_| (0..next(7)).map(|_| symbols[next(symbols.len())]).collect())
+                                              .collect();
+
+            let mut child = std::process::Command::new(dir.join("generated"))
+                                .stdin(std::process::Stdio::piped())
+                                .stdout(std::process::Stdio::piped())
+                                .spawn()
+                                .unwrap();
+            {
+                let stdin = child.stdin.as_mut().unwrap();
+                for input in inputs.iter() {
+                    writeln!(stdin, "{}", input).unwrap();
+                }
+            }
+            let output = String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap();
+
+            let expected: Vec<String> = inputs.iter().map(|i| state_graph.test_sentence(i).to_string()).collect();
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected, "{}", pattern);
+
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    //This test is used to make sure that the grep mode selects, counts and names lines from files and directories
+    #[test]
+    fn test28() {
+        let dir = std::env::temp_dir().join(format!("project1-grep-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("one.txt"), "apple\nbanana\ncherry\n").unwrap();
+        std::fs::write(dir.join("sub").join("two.txt"), b"grape\nan\xffapple pie\n").unwrap();
+
+        let state_graph = graph_for("ap+le");
+        let one = dir.join("one.txt").display().to_string();
+        let two = dir.join("sub").join("two.txt").display().to_string();
+
+        let run = |settings: grep::Settings, paths: Vec<String>| {
+            let mut out: Vec<u8> = Vec::new();
+            let (selected, errors) = grep::Grep::new(&state_graph, settings, &paths).run(&paths, &mut out).unwrap();
+            (String::from_utf8(out).unwrap(), selected, errors)
+        };
+
+        assert_eq!(run(grep::Settings{line_numbers: true, ..Default::default()}, vec![one.clone()]),
+                   ("1:apple\n".to_string(), 1, false));
+        assert_eq!(run(grep::Settings{invert: true, count: true, ..Default::default()}, vec![one.clone()]),
+                   ("2\n".to_string(), 2, false));
+
+        //A link back up the tree is not followed while walking it
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&dir, dir.join("sub").join("loop")).unwrap();
+
+        //Directories are walked in name order with the file names shown
+        let recursive = grep::Settings{recursive: true, line_numbers: true, ..Default::default()};
+        assert_eq!(run(recursive, vec![dir.display().to_string()]),
+                   (format!("{}:1:apple\n{}:2:an\u{FFFD}apple pie\n", one, two), 2, false));
+
+        //A directory without -r and a missing file are errors, the other paths are still searched
+        let (out, selected, errors) = run(grep::Settings{count: true, ..Default::default()},
+                                          vec![dir.display().to_string(), one.clone(), "no-such-file".to_string()]);
+        assert_eq!((out, selected, errors), (format!("{}:1\n", one), 1, true));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    //This test is used to make sure that the stream matcher finds the same match ends however the input is split into chunks
+    #[test]
+    fn test29() {
+        let nfa = nfa::NFA::new_from_regex(&parse("(Σa|b)+|€").expect("Regex Failure:"));
+        let anchored = bytedfa::ByteDFA::new_from_dfa(&dfa::DFA::new_from_nfa(&nfa));
+        let unanchored = bytedfa::ByteDFA::new_from_dfa(&dfa::DFA::new_unanchored(&nfa));
+        let text = "xΣab€bΣΣa€".as_bytes();
+
+        //Every end of a match, found by trying every start with the anchored DFA
+        let mut expected: Vec<usize> = Vec::new();
+        for end in 1..=text.len() {
+            let found = (0..end).any(|start| {
+                let mut matcher = stream::Matcher::new(&anchored);
+                matcher.feed(&text[start..end]);
+                std::str::from_utf8(&text[start..end]).is_ok() && matcher.is_match()
+            });
+            if found {
+                expected.push(end);
+            }
+        }
+        assert_eq!(expected, vec![4, 5, 8, 9, 14, 17]);
+
+        //Split at every pair of places, including inside a character
+        for i in 0..=text.len() {
+            for j in i..=text.len() {
+                let mut matcher = stream::Matcher::new(&unanchored);
+                let mut ends = matcher.feed(&text[..i]);
+                ends.extend(matcher.feed(&text[i..j]));
+                ends.extend(matcher.feed(&text[j..]));
+                ends.extend(matcher.finish());
+                assert_eq!(ends, expected, "split at {} and {}", i, j);
+                assert_eq!(matcher.offset(), text.len());
+            }
+        }
+
+        //The anchored DFA tells whether the whole stream matches
+        let mut matcher = stream::Matcher::new(&anchored);
+        matcher.feed(&"ΣabΣ".as_bytes()[..1]);
+        assert!(!matcher.is_match());
+        matcher.feed(&"ΣabΣ".as_bytes()[1..]);
+        assert!(!matcher.is_match());
+        matcher.feed(b"a");
+        assert!(matcher.is_match());
+
+        //Bytes that are not UTF-8, and a character cut short at the end, are read as U+FFFD
+        let dot = bytedfa::ByteDFA::new_from_dfa(&dfa::DFA::new_unanchored(&nfa::NFA::new_from_regex(&parse("a.b|\u{FFFD}").unwrap())));
+        let mut matcher = stream::Matcher::new(&dot);
+        assert_eq!(matcher.feed(b"a\xffb\xe2\x82"), vec![2, 3]);
+        assert_eq!(matcher.finish(), vec![5]);
+
+        //Reading from an io::Read in chunks gives the same ends
+        let mut matcher = stream::Matcher::new(&unanchored);
+        let mut ends = Vec::new();
+        matcher.scan(text, |end| { ends.push(end); Ok(()) }).unwrap();
+        assert_eq!(ends, expected);
+    }
+
+    //This test is used to make sure that the byte DFA accepts the same inputs as the character DFA, reading invalid UTF-8 as U+FFFD
+    #[test]
+    fn test30() {
+        let patterns = ["[^a]", "(a|Σ)*\\u{10FFFF}", "[\\u{80}-\\u{7FF}\\u{E000}-\\u{10000}]+", ".\\u{FFFD}b", "(?i)[k-m]+$", "^\\u{D7FF}|\\u{E000}"];
+
+        //Pieces of UTF-8, both whole characters and bytes that are not valid on their own
+        let pieces: [&[u8]; 14] = [b"a", b"b", b"k", "Σ".as_bytes(), "K".as_bytes(), "\u{10FFFF}".as_bytes(),
+                                   "\u{D7FF}".as_bytes(), "\u{E000}".as_bytes(), b"\xff", b"\xe2\x82", b"\xed\xa0\x80",
+                                   b"\xf4\x90", b"\xc0", b"\xf0\x9f"];
+
+        for pattern in patterns.iter() {
+            let dfa = dfa::DFA::new_from_nfa(&nfa::NFA::new_from_regex(&parse(pattern).expect("Regex Failure:")));
+            let bytes = bytedfa::ByteDFA::new_from_dfa(&dfa);
+            let graph = StateGraph::new_from_dfa(&dfa);
+
+            //Large classes are a few ranges of bytes, not a state per character
+            assert!(bytes.transitions.len() < 40 * dfa.transitions.len(), "{} has {} states", pattern, bytes.transitions.len());
+
+            //Random strings of pieces
+            let mut next = xorshift(0x2545F4914F6CDD1D);
+            for _ in 0..500 {
+                let mut input: Vec<u8> = Vec::new();
+                for _ in 0..next(6) {
+                    input.extend_from_slice(pieces[next(pieces.len())]);
+                }
+
+                let text = String::from_utf8_lossy(&input);
+                let state = text.chars().fold(graph.start_state, |s, c| graph.next_state(s, c));
+                assert_eq!(bytes.is_match(&input), graph.states[state].accept_state, "{} on {:?}", pattern, input);
+            }
+        }
+    }
+
+    //This test is used to make sure that minimizing keeps the language and that the equivalence check finds a shortest distinguishing string
+    #[test]
+    fn test31() {
+        let minimal = |pattern: &str| dfa::DFA::new_from_nfa(&nfa::NFA::new_from_regex(&parse(pattern).expect("Regex Failure:"))).minimize();
+
+        //Four states for the suffix read so far and one for other characters
+        assert_eq!(minimal("(a|b)*abb").transitions.len(), 5);
+        assert_eq!(minimal("(a|b)*abb").transitions.len(), minimal("(a*b*)*a(b|a)b*b").transitions.len() - 1);
+
+        let same = [("(a|b)*abb", "[ab]*abb"), ("a*", "(a|aa)*"), ("a(b|c)", "ab|ac"), ("^a$", "a"),
+                    ("(?i)k", "[kK\\u{212A}]"), ("x{2,3}", "xx|xxx"), ("(a*b*)*", "[ab]*")];
+        for (a, b) in same.iter() {
+            assert_eq!(equiv::distinguish(&minimal(a), &minimal(b)), None, "{} and {}", a, b);
+        }
+
+        let different = [("x+", "x*", "", false), ("(ab)*", "a*b*", "a", false), ("a|b", "a", "b", true),
+                         ("[^a]", "b", "!", true), ("(a|b)*abb", "(a|b)*ab", "ab", false)];
+        for (a, b, text, first) in different.iter() {
+            let found = equiv::distinguish(&minimal(a), &minimal(b));
+            assert_eq!(found, Some((text.to_string(), *first)), "{} and {}", a, b);
+
+            //The string really is accepted by exactly one of them
+            assert_eq!(graph_for(a).test_sentence(text), *first);
+            assert_eq!(graph_for(b).test_sentence(text), !*first);
+        }
+    }
+
+    //This test is used to check that simplified regexes are smaller and match the same strings
+    #[test]
+    fn test32() {
+        let minimal = |regex: &regex::Regex| dfa::DFA::new_from_nfa(&nfa::NFA::new_from_regex(regex)).minimize();
+
+        let simpler = [("(a|a)*", "a*"), ("(a*)*", "a*"), ("a|ab*", "ab*"), ("ab|ac", "a[b-c]"), ("(a*b*)*", "[a-b]*"),
+                       ("(x+)?", "x*"), ("aa*", "a+"), ("a|b|()", "[a-b]?"), ("hello|help|helm", "hel(lo|[mp])"),
+                       ("xb|yb", "[x-y]b"), ("a{1}b{0,1}c{0}", "ab?"),
+                       ("[^\\u{0}-\\u{10FFFF}]", "[^\\u{0}-\\u{10FFFF}]"), ("a[^\\u{0}-\\u{10FFFF}]|b", "b"),
+                       ("([^\\u{0}-\\u{10FFFF}])*x", "x"), ("[^\\u{0}-\\u{10FFFF}]+|()", "()")];
+        for (pattern, expected) in simpler.iter() {
+            let regex = parse(pattern).expect("Regex Failure:");
+            let simple = simplify::simplify(&regex);
+            assert_eq!(simple.to_string(), *expected);
+
+            //What is written parses back to the same strings
+            let written = parse(&simple.to_string()).expect("Regex Failure:");
+            assert_eq!(equiv::distinguish(&minimal(&regex), &minimal(&written)), None, "{} became {}", pattern, simple);
+        }
+
+        //Random regexes over a few pieces
+        let pieces = ["a", "b", "ab", "()", "[a-c]", "^", "$", "\\.", "[^\\u{0}-\\u{10FFFF}]"];
+        let mut next = xorshift(0x9E3779B97F4A7C15);
+        for _ in 0..500 {
+            let mut stack: Vec<String> = Vec::new();
+            for _ in 0..12 {
+                let op = next(8);
+                let item = match (op, stack.len()) {
+                    (0, n) if n >= 2 => {
+                        let b = stack.pop().unwrap();
+                        format!("({}|{})", stack.pop().unwrap(), b)
+                    },
+                    (1, n) | (2, n) if n >= 2 => {
+                        let b = stack.pop().unwrap();
+                        format!("{}{}", stack.pop().unwrap(), b)
+                    },
+                    (3, n) if n >= 1 => format!("({})*", stack.pop().unwrap()),
+                    (4, n) if n >= 1 => format!("({})+", stack.pop().unwrap()),
+                    (5, n) if n >= 1 => format!("({})?", stack.pop().unwrap()),
+                    _ => pieces[next(pieces.len())].to_string()
+                };
+                stack.push(item);
+            }
+            let pattern = stack.join("|");
+
+            let regex = parse(&pattern).expect("Regex Failure:");
+            let simple = simplify::simplify(&regex);
+            assert!(simple.size() <= regex.size(), "{} became {}", pattern, simple);
+
+            //What is written reads back as a regex matching the same strings
+            let written = parse(&simple.to_string()).expect("Regex Failure:");
+            assert_eq!(equiv::distinguish(&minimal(&regex), &minimal(&written)), None, "{} became {}", pattern, simple);
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the ε-NFA built from a
+//! regular expression using Thompson's construction.
+//!
+//! Each operator of the regex produces a small fragment with a single
+//! entry state and a single dangling exit state. Fragments are glued
+//! together by patching the dangling exit to point at the next fragment.
+
+use crate::charclass::CharClass;
+use crate::regex::{Look, Regex};
+
+// *********************************************************************
+/// Placeholder target for an exit state that has not been patched yet
+const DANGLING: usize = usize::MAX;
+
+// *********************************************************************
+/// A single state of the ε-NFA
+#[derive(Debug, Clone)]
+pub enum State {
+
+    /// Consume any character of the class and move to the next state
+    Class(CharClass, usize),
+
+    /// ε-transitions to both states, the first one is preferred
+    Split(usize, usize),
+
+    /// A single ε-transition
+    Epsilon(usize),
+
+    /// An ε-transition that records the input position in a capture slot
+    Save(usize, usize),
+
+    /// An ε-transition taken only where the anchor matches
+    Look(Look, usize),
+
+    /// The accept state
+    Match,
+}
+
+// *********************************************************************
+/// A partially built piece of the automaton
+///
+/// `end` is always an `Epsilon` state whose target still has to be set.
+struct Fragment {
+    start: usize,
+    end: usize,
+}
+
+// *********************************************************************
+/// # Nondeterministic Finite Automata Structure
+#[derive(Debug)]
+pub struct NFA {
+
+    /// All of the states, referenced by their index
+    pub states: Vec<State>,
+
+    /// Index of the start state
+    pub start: usize,
+}
+
+// *********************************************************************
+/// Implement the methods of the NFA structure
+impl NFA {
+
+    /// Build the ε-NFA for a parsed regex
+    pub fn new_from_regex(regex: &Regex) -> NFA {
+
+        let mut nfa = NFA{states: vec!(), start: 0};
+
+        // Group 0 records the span of the whole match
+        let whole = nfa.compile(regex);
+        let whole = nfa.capture(0, whole);
+        let accept = nfa.push(State::Match);
+        nfa.patch(whole.end, accept);
+        nfa.start = whole.start;
+
+        nfa
+    }
+
+    /// Build one ε-NFA matching any of the regexes, giving each regex an
+    /// accept state of its own
+    ///
+    /// Also returns the accept states in the order of the regexes. No
+    /// capture groups are recorded.
+    pub fn new_from_regexes(regexes: &[Regex]) -> (NFA, Vec<usize>) {
+
+        let mut nfa = NFA{states: vec!(), start: 0};
+        let mut accepts: Vec<usize> = Vec::new();
+        let mut start: Option<usize> = None;
+
+        for regex in regexes {
+            let f = nfa.compile(regex);
+            let accept = nfa.push(State::Match);
+            nfa.patch(f.end, accept);
+            accepts.push(accept);
+
+            // Try the regexes one after another from the start
+            start = Some(match start {
+                Some(s) => nfa.push(State::Split(s, f.start)),
+                None => f.start
+            });
+        }
+
+        // With no regexes the start state matches nothing
+        nfa.start = match start {
+            Some(s) => s,
+            None => nfa.symbol(CharClass::new(vec!())).start
+        };
+
+        (nfa, accepts)
+    }
+
+    /// Build the fragment for a node of the syntax tree
+    fn compile(&mut self, regex: &Regex) -> Fragment {
+
+        match regex {
+            Regex::Literal(c) => self.symbol(CharClass::single(*c)),
+            Regex::Class(class) => self.symbol(class.clone()),
+            Regex::Look(look) => {
+                let end = self.push(State::Epsilon(DANGLING));
+                let start = self.push(State::Look(*look, end));
+                Fragment{start, end}
+            },
+            Regex::Concat(items) => {
+                let mut sequence: Option<Fragment> = None;
+                for item in items {
+                    let f = self.compile(item);
+                    sequence = Some(self.append(sequence, f));
+                }
+                match sequence {
+                    Some(f) => f,
+                    None => self.empty()
+                }
+            },
+            Regex::Alternation(items) => {
+                let mut choice: Option<Fragment> = None;
+                for item in items {
+                    let f = self.compile(item);
+                    choice = Some(match choice {
+                        Some(first) => self.alternate(first, f),
+                        None => f
+                    });
+                }
+                match choice {
+                    Some(f) => f,
+                    None => self.empty()
+                }
+            },
+            Regex::Star(inner) => {
+                let f = self.compile(inner);
+                self.star(f)
+            },
+            Regex::Plus(inner) => {
+                let f = self.compile(inner);
+                self.plus(f)
+            },
+            Regex::Optional(inner) => {
+                let f = self.compile(inner);
+                self.optional(f)
+            },
+            Regex::Repeat(inner, min, max) => self.repeat(inner, *min, *max),
+            Regex::Group(group, inner) => {
+                let f = self.compile(inner);
+                self.capture(*group, f)
+            }
+        }
+    }
+
+    /// Wrap a fragment in the pair of states saving where group `group`
+    /// starts (slot 2 * group) and ends (slot 2 * group + 1)
+    fn capture(&mut self, group: usize, body: Fragment) -> Fragment {
+        let end = self.push(State::Epsilon(DANGLING));
+        let close = self.push(State::Save(2 * group + 1, end));
+        self.patch(body.end, close);
+        let start = self.push(State::Save(2 * group, body.start));
+        Fragment{start, end}
+    }
+
+    /// Fragment matching between `min` and `max` occurrences, written out
+    /// as `min` copies followed by either a star or nested optionals
+    ///
+    /// `a{2,4}` becomes `aa(a(a)?)?`
+    fn repeat(&mut self, inner: &Regex, min: usize, max: Option<usize>) -> Fragment {
+
+        let mut sequence: Option<Fragment> = None;
+        for _ in 0..min {
+            let f = self.compile(inner);
+            sequence = Some(self.append(sequence, f));
+        }
+
+        let tail = match max {
+            None => {
+                let f = self.compile(inner);
+                Some(self.star(f))
+            },
+            Some(m) => {
+                let mut tail: Option<Fragment> = None;
+                for _ in min..m {
+                    let f = self.compile(inner);
+                    let f = match tail {
+                        Some(t) => self.concat(f, t),
+                        None => f
+                    };
+                    tail = Some(self.optional(f));
+                }
+                tail
+            }
+        };
+
+        if let Some(t) = tail {
+            sequence = Some(self.append(sequence, t));
+        }
+
+        match sequence {
+            Some(f) => f,
+            None => self.empty()
+        }
+    }
+
+    /// Add a state returning its index
+    fn push(&mut self, state: State) -> usize {
+        self.states.push(state);
+        self.states.len() - 1
+    }
+
+    /// Point a dangling exit state at its target
+    fn patch(&mut self, end: usize, target: usize) {
+        self.states[end] = State::Epsilon(target);
+    }
+
+    /// Fragment matching nothing but the empty string
+    fn empty(&mut self) -> Fragment {
+        let end = self.push(State::Epsilon(DANGLING));
+        Fragment{start: end, end}
+    }
+
+    /// Fragment matching a single symbol from the class
+    fn symbol(&mut self, class: CharClass) -> Fragment {
+        let end = self.push(State::Epsilon(DANGLING));
+        let start = self.push(State::Class(class, end));
+        Fragment{start, end}
+    }
+
+    /// Concatenate a fragment onto an optional sequence
+    fn append(&mut self, sequence: Option<Fragment>, next: Fragment) -> Fragment {
+        match sequence {
+            Some(first) => self.concat(first, next),
+            None => next
+        }
+    }
+
+    /// Fragment matching `first` followed by `second`
+    fn concat(&mut self, first: Fragment, second: Fragment) -> Fragment {
+        self.patch(first.end, second.start);
+        Fragment{start: first.start, end: second.end}
+    }
+
+    /// Fragment matching either `first` or `second`
+    fn alternate(&mut self, first: Fragment, second: Fragment) -> Fragment {
+        let end = self.push(State::Epsilon(DANGLING));
+        self.patch(first.end, end);
+        self.patch(second.end, end);
+        let start = self.push(State::Split(first.start, second.start));
+        Fragment{start, end}
+    }
+
+    /// Fragment matching zero or more repetitions
+    fn star(&mut self, body: Fragment) -> Fragment {
+        let end = self.push(State::Epsilon(DANGLING));
+        let start = self.push(State::Split(body.start, end));
+        self.patch(body.end, start);
+        Fragment{start, end}
+    }
+
+    /// Fragment matching one or more repetitions
+    fn plus(&mut self, body: Fragment) -> Fragment {
+        let end = self.push(State::Epsilon(DANGLING));
+        let repeat = self.push(State::Split(body.start, end));
+        self.patch(body.end, repeat);
+        Fragment{start: body.start, end}
+    }
+
+    /// Fragment matching zero or one occurrence
+    fn optional(&mut self, body: Fragment) -> Fragment {
+        let end = self.push(State::Epsilon(DANGLING));
+        self.patch(body.end, end);
+        let start = self.push(State::Split(body.start, end));
+        Fragment{start, end}
+    }
+
+    /// The classes appearing on transitions
+    ///
+    /// Newline gets a class of its own when a line anchor needs to tell it
+    /// apart from the other characters.
+    pub fn classes(&self) -> Vec<CharClass> {
+        let mut classes: Vec<CharClass> = Vec::new();
+        for state in self.states.iter() {
+            let class = match state {
+                State::Class(class, _) => class.clone(),
+                State::Look(Look::LineStart, _) | State::Look(Look::LineEnd, _) => CharClass::single('\n'),
+                _ => continue
+            };
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        classes
+    }
+
+    /// Follow ε-transitions from a set of states, where `prev` is the
+    /// character before the current position (None at the start)
+    ///
+    /// Only the states that consume input or accept are kept, sorted,
+    /// so that equal sets compare equal. `^` is decided here; `$` depends
+    /// on what comes next, so its states are kept to be decided later.
+    /// Since a `^` may follow such a `$`, a set keeping one after a newline
+    /// or at the start of the text also holds a marker past the last state
+    /// telling which `^` anchors hold there.
+    pub fn closure(&self, states: &[usize], prev: Option<char>) -> Vec<usize> {
+        let start = (prev.is_none(), prev.is_none_or(|c| c == '\n'));
+        let mut set = self.follow(states, |look| matches!(look, Look::Start | Look::LineStart) && holds(look, start, None));
+        if start.1 && set.iter().any(|s| matches!(self.states[*s], State::Look(..))) {
+            set.push(self.states.len() + usize::from(start.0));
+        }
+        set
+    }
+
+    /// The states of a set without its marker, and whether the position is
+    /// at the start of the text and at the start of a line
+    fn unmark(&self, states: &[usize]) -> (Vec<usize>, (bool, bool)) {
+        match states.last() {
+            Some(m) if *m >= self.states.len() => (states[..states.len() - 1].to_vec(), (*m > self.states.len(), true)),
+            _ => (states.to_vec(), (false, false))
+        }
+    }
+
+    /// The closure taken at the start of the text
+    pub fn start_set(&self) -> Vec<usize> {
+        self.closure(&[self.start], None)
+    }
+
+    /// The closure taken after a character other than newline
+    pub fn start_set_inside(&self) -> Vec<usize> {
+        self.follow(&[self.start], |_| false)
+    }
+
+    /// Follow ε-transitions, passing the anchors for which `passes` is true
+    /// and keeping the `$` states that do not pass
+    fn follow<P: Fn(Look) -> bool>(&self, states: &[usize], passes: P) -> Vec<usize> {
+
+        let mut seen = vec![false; self.states.len()];
+        let mut stack: Vec<usize> = states.to_vec();
+        let mut set: Vec<usize> = Vec::new();
+
+        while let Some(s) = stack.pop() {
+            if seen[s] {
+                continue;
+            }
+            seen[s] = true;
+
+            match self.states[s] {
+                State::Split(a, b) => {
+                    stack.push(b);
+                    stack.push(a);
+                },
+                State::Epsilon(t) | State::Save(_, t) => stack.push(t),
+                State::Look(look, t) if passes(look) => stack.push(t),
+                State::Look(Look::End, _) | State::Look(Look::LineEnd, _) => set.push(s),
+                State::Look(..) => (),
+                State::Class(..) | State::Match => set.push(s)
+            }
+        }
+
+        set.sort();
+        set
+    }
+
+    /// The closure of the states reached by consuming `c`
+    ///
+    /// The anchors waiting in the set are passed first when they hold
+    /// before `c`.
+    pub fn step(&self, states: &[usize], c: char) -> Vec<usize> {
+        let (states, start) = self.unmark(states);
+        let states = self.follow(&states, |look| holds(look, start, Some(c)));
+        let next: Vec<usize> = states.iter()
+                                     .filter_map(|s| match self.states[*s] {
+                                         State::Class(ref class, t) if class.contains(c) => Some(t),
+                                         _ => None
+                                     })
+                                     .collect();
+        self.closure(&next, Some(c))
+    }
+
+    /// Number of capture slots used, two per group including group 0
+    pub fn slots(&self) -> usize {
+        self.states
+            .iter()
+            .filter_map(|s| match s {
+                State::Save(slot, _) => Some(slot + 1),
+                _ => None
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Does the set accept when the text ends here
+    pub fn is_match(&self, states: &[usize]) -> bool {
+        !self.matches(states).is_empty()
+    }
+
+    /// The accept states in the set when the text ends here
+    pub fn matches(&self, states: &[usize]) -> Vec<usize> {
+        let (states, start) = self.unmark(states);
+        self.follow(&states, |look| holds(look, start, None))
+            .into_iter()
+            .filter(|s| matches!(self.states[*s], State::Match))
+            .collect()
+    }
+
+    /// Does the set accept when a character other than newline follows
+    pub fn is_match_inside(&self, states: &[usize]) -> bool {
+        states.iter().any(|s| matches!(self.states.get(*s), Some(State::Match)))
+    }
+
+}
+
+// *********************************************************************
+/// Does an anchor hold where `start` tells whether the text and the line
+/// start, and `next` is the character after (None at the end)
+fn holds(look: Look, start: (bool, bool), next: Option<char>) -> bool {
+    match look {
+        Look::Start => start.0,
+        Look::LineStart => start.1,
+        Look::End => next.is_none(),
+        Look::LineEnd => next.is_none_or(|c| c == '\n')
+    }
+}
@@ -0,0 +1,138 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the Pike VM, which simulates
+//! the ε-NFA directly to find out what each capture group matched.
+//!
+//! A thread is an NFA state together with the capture slots recorded on
+//! the way to it. All threads advance one character at a time in priority
+//! order, and when two threads reach the same state only the higher
+//! priority one is kept, so the run takes time proportional to the length
+//! of the input times the number of states.
+
+use std::ops::Range;
+
+use crate::nfa::{self, NFA};
+use crate::regex::Look;
+
+// *********************************************************************
+/// Capture slots of a thread, None until the Save state is passed
+type Slots = Vec<Option<usize>>;
+
+// *********************************************************************
+/// # Pike VM Structure
+pub struct PikeVM {
+
+    /// The automaton being simulated
+    nfa: NFA,
+
+    /// Number of capture slots, two per group including group 0
+    slots: usize,
+}
+
+// *********************************************************************
+/// Implement the methods of the PikeVM structure
+impl PikeVM {
+
+    /// Create a VM running the given ε-NFA
+    pub fn new(nfa: NFA) -> PikeVM {
+        let slots = nfa.slots();
+        PikeVM{nfa, slots}
+    }
+
+    /// Number of capture groups, not counting group 0
+    pub fn groups(&self) -> usize {
+        (self.slots / 2).saturating_sub(1)
+    }
+
+    /// Match the whole text returning the span of every group
+    ///
+    /// Entry 0 is the whole text, entry n is what group n matched last,
+    /// None for a group that took no part in the match. Returns None when
+    /// the text is rejected. When there are several ways to match, groups
+    /// are assigned the way a backtracking matcher would: the left side of
+    /// `|` is preferred and repetitions are greedy.
+    pub fn captures(&self, text: &str) -> Option<Vec<Option<Range<usize>>>> {
+
+        let mut current: Vec<(usize, Slots)> = Vec::new();
+        let mut seen = vec![false; self.nfa.states.len()];
+        self.add_thread(&mut current, &mut seen, self.nfa.start, vec![None; self.slots], text, 0);
+
+        for (i, ch) in text.char_indices() {
+
+            let mut next: Vec<(usize, Slots)> = Vec::new();
+            let mut seen = vec![false; self.nfa.states.len()];
+
+            for (state, slots) in current.into_iter() {
+                if let nfa::State::Class(ref class, target) = self.nfa.states[state] {
+                    if class.contains(ch) {
+                        self.add_thread(&mut next, &mut seen, target, slots, text, i + ch.len_utf8());
+                    }
+                }
+            }
+
+            if next.is_empty() {
+                return None;
+            }
+            current = next;
+        }
+
+        // The highest priority thread that has reached the accept state wins
+        current.into_iter()
+               .find(|(state, _)| matches!(self.nfa.states[*state], nfa::State::Match))
+               .map(|(_, slots)| {
+                   slots.chunks(2)
+                        .map(|pair| match (pair[0], pair[1]) {
+                            (Some(start), Some(end)) => Some(start..end),
+                            _ => None
+                        })
+                        .collect()
+               })
+    }
+
+    /// Add a thread for `state` to the list, following ε-transitions in
+    /// priority order and recording the position `pos` in capture slots
+    fn add_thread(&self, list: &mut Vec<(usize, Slots)>, seen: &mut [bool],
+                  state: usize, slots: Slots, text: &str, pos: usize) {
+
+        // Depth first with an explicit stack, the preferred branch is pushed
+        // last so it is explored first
+        let mut stack: Vec<(usize, Slots)> = vec![(state, slots)];
+
+        while let Some((s, mut slots)) = stack.pop() {
+            if seen[s] {
+                continue;
+            }
+            seen[s] = true;
+
+            match self.nfa.states[s] {
+                nfa::State::Split(a, b) => {
+                    stack.push((b, slots.clone()));
+                    stack.push((a, slots));
+                },
+                nfa::State::Epsilon(t) => stack.push((t, slots)),
+                nfa::State::Look(look, t) => {
+                    if matches_at(look, text, pos) {
+                        stack.push((t, slots));
+                    }
+                },
+                nfa::State::Save(slot, t) => {
+                    slots[slot] = Some(pos);
+                    stack.push((t, slots));
+                },
+                nfa::State::Class(..) | nfa::State::Match => list.push((s, slots))
+            }
+        }
+    }
+
+}
+
+// *********************************************************************
+/// Does the anchor match at byte offset `pos` of the text
+fn matches_at(look: Look, text: &str, pos: usize) -> bool {
+    match look {
+        Look::Start => pos == 0,
+        Look::End => pos == text.len(),
+        Look::LineStart => pos == 0 || text[..pos].ends_with('\n'),
+        Look::LineEnd => pos == text.len() || text[pos..].starts_with('\n')
+    }
+}
@@ -0,0 +1,736 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with the regex abstract syntax
+//! tree and the recursive descent parser that builds it.
+//!
+//! Grammar, lowest precedence first:
+//!
+//! ```text
+//! alternation := concat ('|' concat)*
+//! concat      := repeat repeat*
+//! repeat      := atom ('*' | '+' | '?' | count)*
+//! count       := '{' n '}' | '{' n ',' '}' | '{' n ',' m '}'
+//! atom        := symbol | '.' | '^' | '$' | escape | class | flags
+//!              | '(' alternation ')' | '(' ')'
+//! flags       := '(?' ('i' | 'm' | 's')* ('-' ('i' | 'm' | 's')*)? ')'
+//! escape      := '\' punctuation | '\n' | '\t' | '\r' | '\u{' hex '}' | perl
+//! perl        := '\w' | '\d' | '\s' | '\W' | '\D' | '\S'
+//! class       := '[' '^'? item+ ']'
+//! item        := symbol | symbol '-' symbol | perl
+//! ```
+//!
+//! A backslash in front of any symbol that is not a letter or digit makes
+//! it literal, so `\*`, `\|`, `\(` and `\\` match `*`, `|`, `(` and `\`.
+//! `.` matches any symbol but newline, `^` and `$` match at the start and
+//! end of the text. Every other symbol, `Σ` included, is always literal.
+//!
+//! Flags change how the rest of the enclosing group is read: `i` ignores
+//! case, `m` makes `^` and `$` match at the start and end of every line
+//! and `s` lets `.` match newline. Flags after a `-` are turned off.
+//! Ignoring case is done here by replacing each symbol with the class of
+//! its case variants, so the automata never see the flag.
+//!
+//! The repetition operators bind tightest, then concatenation, then `|`,
+//! so `ab*|c` is read as `(a(b*))|c`.
+
+use std::fmt;
+
+use crate::charclass::CharClass;
+
+// *********************************************************************
+/// # Regex Abstract Syntax Tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Regex {
+
+    /// A single symbol
+    Literal(char),
+
+    /// Any one symbol of the class
+    Class(CharClass),
+
+    /// Matches the empty string where the condition holds
+    Look(Look),
+
+    /// Each item followed by the next
+    Concat(Vec<Regex>),
+
+    /// Any one of the items
+    Alternation(Vec<Regex>),
+
+    /// Zero or more repetitions
+    Star(Box<Regex>),
+
+    /// One or more repetitions
+    Plus(Box<Regex>),
+
+    /// Zero or one occurrence
+    Optional(Box<Regex>),
+
+    /// Between `min` and `max` occurrences, with no upper bound when `max`
+    /// is None
+    Repeat(Box<Regex>, usize, Option<usize>),
+
+    /// A parenthesized sub expression with its capture group number,
+    /// counting opening parentheses from 1
+    Group(usize, Box<Regex>),
+}
+
+// *********************************************************************
+/// Implement the methods of the Regex syntax tree
+impl Regex {
+
+    /// Number of symbol occurrences once counted repetitions are written
+    /// out in full, which is what the size of the automaton grows with
+    pub fn size(&self) -> usize {
+        match self {
+            Regex::Literal(_) | Regex::Class(_) | Regex::Look(_) => 1,
+            Regex::Concat(items) | Regex::Alternation(items) => {
+                items.iter().fold(0, |n, r| n.saturating_add(r.size()))
+            },
+            Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) | Regex::Group(_, inner) => {
+                inner.size()
+            },
+            Regex::Repeat(inner, min, max) => {
+                inner.size().saturating_mul(max.unwrap_or_else(|| min.saturating_add(1)))
+            }
+        }
+    }
+
+    /// Does the regex contain `^` or `$`
+    pub fn has_looks(&self) -> bool {
+        match self {
+            Regex::Look(_) => true,
+            Regex::Literal(_) | Regex::Class(_) => false,
+            Regex::Concat(items) | Regex::Alternation(items) => items.iter().any(|r| r.has_looks()),
+            Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner)
+                | Regex::Group(_, inner) | Regex::Repeat(inner, _, _) => inner.has_looks()
+        }
+    }
+
+}
+
+// *********************************************************************
+/// Write a symbol outside of brackets so that it reads back as itself
+fn write_literal(f: &mut fmt::Formatter<'_>, c: char) -> fmt::Result {
+    match c {
+        '\n' => write!(f, "\\n"),
+        '\t' => write!(f, "\\t"),
+        '\r' => write!(f, "\\r"),
+        c if c.is_control() || c.is_whitespace() => write!(f, "\\u{{{:X}}}", c as u32),
+        c if c.is_alphanumeric() => write!(f, "{}", c),
+        c => write!(f, "\\{}", c)
+    }
+}
+
+// *********************************************************************
+/// Write a sub expression, adding parentheses when an operator binding at
+/// least as tight as `level` surrounds it: 0 alternation, 1 concatenation,
+/// 2 repetition
+fn write_sub(f: &mut fmt::Formatter<'_>, regex: &Regex, level: u8) -> fmt::Result {
+    let parens = match regex {
+        Regex::Alternation(items) => level > 0 && items.len() > 1,
+        Regex::Concat(items) => level > 1 && items.len() > 1,
+        _ => false
+    };
+    if parens {
+        write!(f, "({})", regex)
+    } else {
+        write!(f, "{}", regex)
+    }
+}
+
+// *********************************************************************
+/// Display the regex in the syntax `parse` reads, so that parsing it again
+/// gives a regex matching the same strings
+impl fmt::Display for Regex {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Regex::Literal(c) => write_literal(f, *c),
+            Regex::Class(class) => match class.ranges() {
+                [(lo, hi)] if lo == hi => write_literal(f, *lo),
+
+                // `[]` does not parse, a class with every character left out does
+                [] => write!(f, "[^\\u{{0}}-\\u{{10FFFF}}]"),
+                _ => write!(f, "{}", class)
+            },
+            Regex::Look(Look::Start) => write!(f, "^"),
+            Regex::Look(Look::End) => write!(f, "$"),
+            Regex::Look(Look::LineStart) => write!(f, "((?m)^)"),
+            Regex::Look(Look::LineEnd) => write!(f, "((?m)$)"),
+            Regex::Concat(items) if items.is_empty() => write!(f, "()"),
+            Regex::Concat(items) => {
+                for item in items {
+                    write_sub(f, item, 1)?;
+                }
+                Ok(())
+            },
+            Regex::Alternation(items) => {
+                for (n, item) in items.iter().enumerate() {
+                    if n > 0 {
+                        write!(f, "|")?;
+                    }
+                    write_sub(f, item, 0)?;
+                }
+                Ok(())
+            },
+            Regex::Star(inner) => {
+                write_sub(f, inner, 2)?;
+                write!(f, "*")
+            },
+            Regex::Plus(inner) => {
+                write_sub(f, inner, 2)?;
+                write!(f, "+")
+            },
+            Regex::Optional(inner) => {
+                write_sub(f, inner, 2)?;
+                write!(f, "?")
+            },
+            Regex::Repeat(inner, min, max) => {
+                write_sub(f, inner, 2)?;
+                match max {
+                    None => write!(f, "{{{},}}", min),
+                    Some(max) if max == min => write!(f, "{{{}}}", min),
+                    Some(max) => write!(f, "{{{},{}}}", min, max)
+                }
+            },
+            Regex::Group(_, inner) => write!(f, "({})", inner)
+        }
+    }
+
+}
+
+// *********************************************************************
+/// Where an anchor matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Look {
+
+    /// `^`: the start of the text
+    Start,
+
+    /// `$`: the end of the text
+    End,
+
+    /// `^` with the m flag: the start of the text or just after a newline
+    LineStart,
+
+    /// `$` with the m flag: the end of the text or just before a newline
+    LineEnd,
+}
+
+// *********************************************************************
+/// # Regex Flags
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Flags {
+
+    /// i: letters match either case
+    pub case_insensitive: bool,
+
+    /// m: `^` and `$` match at line boundaries
+    pub multiline: bool,
+
+    /// s: `.` matches newline
+    pub dot_all: bool,
+}
+
+// *********************************************************************
+/// # Regex Syntax Settings
+#[derive(Debug, Clone)]
+pub struct Syntax {
+
+    /// Most symbol occurrences a counted repetition may expand to
+    pub repeat_limit: usize,
+
+    /// Flags in effect at the start of the pattern
+    pub flags: Flags,
+}
+
+// *********************************************************************
+impl Default for Syntax {
+
+    fn default() -> Syntax {
+        Syntax{repeat_limit: 500, flags: Flags::default()}
+    }
+
+}
+
+// *********************************************************************
+/// # Regex Syntax Error
+///
+/// Records where in the pattern the problem was found so that it can be
+/// pointed at with a caret.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegexError {
+
+    /// The pattern that failed to parse
+    pub pattern: String,
+
+    /// Byte offset of the problem within the pattern
+    pub offset: usize,
+
+    /// Description of the problem
+    pub message: String,
+}
+
+// *********************************************************************
+/// Display the error followed by the pattern with a caret under the
+/// offending position
+impl fmt::Display for RegexError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+
+        // The caret is placed by character so multi byte symbols line up
+        let column = self.pattern[..self.offset].chars().count();
+
+        writeln!(f, "Regex error at offset {}: {}", self.offset, self.message)?;
+        writeln!(f, "    {}", self.pattern)?;
+        write!(f, "    {}^", " ".repeat(column))
+    }
+
+}
+
+// *********************************************************************
+/// Parse a regex with the given settings returning its syntax tree
+pub fn parse(pattern: &str, syntax: &Syntax) -> Result<Regex, RegexError> {
+
+    let mut parser = Parser{pattern,
+                            syntax,
+                            chars: pattern.char_indices().collect(),
+                            pos: 0,
+                            groups: 0,
+                            flags: syntax.flags};
+
+    let regex = parser.alternation()?;
+
+    // Only an unmatched `)` can stop the top level alternation early
+    if let Some(c) = parser.peek() {
+        return Err(parser.error(format!("Unmatched {}", c)));
+    }
+
+    // Each count is checked as it is read, but copies written out by counts
+    // in different parts of the pattern add up too, so the whole regex may
+    // only grow past the length of the pattern by the limit
+    if regex.size() > syntax.repeat_limit.saturating_add(parser.chars.len()) {
+        return Err(parser.error_at(0, format!("Regex expands to more than the limit of {} symbols",
+                                              syntax.repeat_limit)));
+    }
+
+    Ok(regex)
+}
+
+// *********************************************************************
+/// State of the recursive descent parser
+struct Parser<'a> {
+
+    /// The pattern being parsed
+    pattern: &'a str,
+
+    /// Settings the pattern is parsed with
+    syntax: &'a Syntax,
+
+    /// The characters of the pattern with their byte offsets
+    chars: Vec<(usize, char)>,
+
+    /// Index of the next character in `chars`
+    pos: usize,
+
+    /// Number of capture groups opened so far
+    groups: usize,
+
+    /// Flags in effect at the current position
+    flags: Flags,
+}
+
+// *********************************************************************
+impl<'a> Parser<'a> {
+
+    /// The next character without consuming it
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).map(|c| c.1)
+    }
+
+    /// Byte offset of the next character (the pattern length at the end)
+    fn offset(&self) -> usize {
+        match self.chars.get(self.pos) {
+            Some(c) => c.0,
+            None => self.pattern.len()
+        }
+    }
+
+    /// Build an error at the current position
+    fn error(&self, message: String) -> RegexError {
+        self.error_at(self.offset(), message)
+    }
+
+    /// Build an error at the given byte offset
+    fn error_at(&self, offset: usize, message: String) -> RegexError {
+        RegexError{pattern: self.pattern.to_string(), offset, message}
+    }
+
+    /// alternation := concat ('|' concat)*
+    fn alternation(&mut self) -> Result<Regex, RegexError> {
+
+        let mut items = vec![self.concat()?];
+
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            items.push(self.concat()?);
+        }
+
+        Ok(if items.len() == 1 { items.remove(0) } else { Regex::Alternation(items) })
+    }
+
+    /// concat := repeat repeat*
+    fn concat(&mut self) -> Result<Regex, RegexError> {
+
+        let mut items = Vec::new();
+
+        loop {
+            match self.peek() {
+                None | Some('|') | Some(')') => break,
+                _ => items.push(self.repeat()?)
+            }
+        }
+
+        match items.len() {
+            0 => Err(self.error(match self.peek() {
+                     None => "Expected a symbol before the end of the regex".to_string(),
+                     Some(c) => format!("Expected a symbol before {}", c)
+                 })),
+            1 => Ok(items.remove(0)),
+            _ => Ok(Regex::Concat(items))
+        }
+    }
+
+    /// repeat := atom ('*' | '+' | '?' | count)*
+    fn repeat(&mut self) -> Result<Regex, RegexError> {
+
+        let mut regex = self.atom()?;
+
+        loop {
+            let start = self.offset();
+
+            regex = match self.peek() {
+                Some('*') => {
+                    self.pos += 1;
+                    Regex::Star(Box::new(regex))
+                },
+                Some('+') => {
+                    self.pos += 1;
+                    Regex::Plus(Box::new(regex))
+                },
+                Some('?') => {
+                    self.pos += 1;
+                    Regex::Optional(Box::new(regex))
+                },
+                Some('{') => {
+                    let (min, max) = self.count()?;
+                    let repeated = Regex::Repeat(Box::new(regex), min, max);
+
+                    // Refuse to write out more copies than the limit allows
+                    if repeated.size() > self.syntax.repeat_limit {
+                        return Err(self.error_at(start,
+                            format!("Repetition expands to more than the limit of {} symbols",
+                                    self.syntax.repeat_limit)));
+                    }
+                    repeated
+                },
+                _ => return Ok(regex)
+            };
+        }
+    }
+
+    /// count := '{' n '}' | '{' n ',' '}' | '{' n ',' m '}'
+    fn count(&mut self) -> Result<(usize, Option<usize>), RegexError> {
+
+        let start = self.offset();
+        self.pos += 1;
+
+        let min = self.number()?;
+        let max = match self.peek() {
+            Some('}') => Some(min),
+            Some(',') => {
+                self.pos += 1;
+                if self.peek() == Some('}') {
+                    None
+                } else {
+                    Some(self.number()?)
+                }
+            },
+            _ => return Err(self.error("Expected , or } in the repetition count".to_string()))
+        };
+
+        if self.peek() != Some('}') {
+            return Err(self.error("Expected } to close the repetition count".to_string()));
+        }
+        self.pos += 1;
+
+        if let Some(m) = max {
+            if m < min {
+                return Err(self.error_at(start, format!("Repetition count {{{},{}}} is out of order", min, m)));
+            }
+        }
+
+        Ok((min, max))
+    }
+
+    /// A run of decimal digits inside a repetition count
+    fn number(&mut self) -> Result<usize, RegexError> {
+
+        let digits_start = self.offset();
+        let mut digits = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_digit()) {
+            digits.push(c);
+            self.pos += 1;
+        }
+
+        if digits.is_empty() {
+            return Err(self.error("Expected a number in the repetition count".to_string()));
+        }
+
+        digits.parse::<usize>()
+              .map_err(|_| self.error_at(digits_start, format!("Repetition count {} is too large", digits)))
+    }
+
+    /// atom := symbol | class | '(' alternation ')' | '(' ')'
+    fn atom(&mut self) -> Result<Regex, RegexError> {
+
+        let start = self.offset();
+
+        match self.peek() {
+            Some('(') if self.chars.get(self.pos + 1).map(|c| c.1) == Some('?') => self.flag_group(),
+            Some('(') => {
+                self.pos += 1;
+                self.groups += 1;
+                let group = self.groups;
+
+                // An empty group matches only the empty string
+                if self.peek() == Some(')') {
+                    self.pos += 1;
+                    return Ok(Regex::Group(group, Box::new(Regex::Concat(vec!()))));
+                }
+
+                // Flags set inside the group end with it
+                let flags = self.flags;
+                let inner = self.alternation()?;
+                self.flags = flags;
+
+                if self.peek() != Some(')') {
+                    return Err(self.error_at(start, "Unclosed group".to_string()));
+                }
+                self.pos += 1;
+                Ok(Regex::Group(group, Box::new(inner)))
+            },
+            Some('[') => self.bracket(),
+            Some('\\') => {
+                self.pos += 1;
+                let escaped = self.escape(start)?;
+                Ok(self.fold(escaped))
+            },
+            Some('.') => {
+                self.pos += 1;
+                Ok(Regex::Class(if self.flags.dot_all { CharClass::any() } else { CharClass::dot() }))
+            },
+            Some('^') => {
+                self.pos += 1;
+                Ok(Regex::Look(if self.flags.multiline { Look::LineStart } else { Look::Start }))
+            },
+            Some('$') => {
+                self.pos += 1;
+                Ok(Regex::Look(if self.flags.multiline { Look::LineEnd } else { Look::End }))
+            },
+            Some(c) if c == '*' || c == '+' || c == '?' || c == '{' => {
+                Err(self.error(format!("Operator {} has nothing to repeat", c)))
+            },
+            Some(c) => {
+                self.pos += 1;
+                Ok(self.fold(Regex::Literal(c)))
+            },
+            None => Err(self.error("Unexpected end of the regex".to_string()))
+        }
+    }
+
+    /// flags := '(?' ('i' | 'm' | 's')* ('-' ('i' | 'm' | 's')*)? ')'
+    ///
+    /// Matches the empty string, the flags apply from here to the end of
+    /// the enclosing group.
+    fn flag_group(&mut self) -> Result<Regex, RegexError> {
+
+        let start = self.offset();
+        self.pos += 2;
+
+        let mut on = true;
+        loop {
+            match self.peek() {
+                Some('i') => self.flags.case_insensitive = on,
+                Some('m') => self.flags.multiline = on,
+                Some('s') => self.flags.dot_all = on,
+                Some('-') if on => on = false,
+                Some(')') => break,
+                Some(c) => return Err(self.error(format!("Unknown flag {}", c))),
+                None => return Err(self.error_at(start, "Unclosed flag group".to_string()))
+            }
+            self.pos += 1;
+        }
+        self.pos += 1;
+
+        Ok(Regex::Concat(vec!()))
+    }
+
+    /// A symbol or class replaced by the class of its case variants when
+    /// case is ignored
+    fn fold(&self, regex: Regex) -> Regex {
+
+        if !self.flags.case_insensitive {
+            return regex;
+        }
+
+        let folded = match &regex {
+            Regex::Literal(c) => CharClass::single(*c).case_fold(),
+            Regex::Class(class) => class.case_fold(),
+            _ => return regex
+        };
+
+        // A symbol without case variants stays a symbol
+        match regex {
+            Regex::Literal(c) if folded == CharClass::single(c) => regex,
+            _ => Regex::Class(folded)
+        }
+    }
+
+    /// The escape after a backslash at byte offset `start`
+    ///
+    /// Returns a literal for escaped symbols or a class for `\w` and friends.
+    fn escape(&mut self, start: usize) -> Result<Regex, RegexError> {
+
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error_at(start, "Trailing backslash".to_string()))
+        };
+        self.pos += 1;
+
+        Ok(match c {
+            'w' => Regex::Class(CharClass::word()),
+            'd' => Regex::Class(CharClass::digit()),
+            's' => Regex::Class(CharClass::space()),
+            'W' => Regex::Class(CharClass::word().negate()),
+            'D' => Regex::Class(CharClass::digit().negate()),
+            'S' => Regex::Class(CharClass::space().negate()),
+            'n' => Regex::Literal('\n'),
+            't' => Regex::Literal('\t'),
+            'r' => Regex::Literal('\r'),
+            'u' => Regex::Literal(self.code_point(start)?),
+            c if !c.is_alphanumeric() => Regex::Literal(c),
+            c => return Err(self.error_at(start, format!("Unknown escape \\{}", c)))
+        })
+    }
+
+    /// The `{hex}` part of a `\u{hex}` escape starting at byte offset `start`
+    fn code_point(&mut self, start: usize) -> Result<char, RegexError> {
+
+        if self.peek() != Some('{') {
+            return Err(self.error("Expected { after \\u".to_string()));
+        }
+        self.pos += 1;
+
+        let mut digits = String::new();
+        while let Some(c) = self.peek().filter(|c| *c != '}') {
+            digits.push(c);
+            self.pos += 1;
+        }
+        if self.peek() != Some('}') {
+            return Err(self.error_at(start, "Unclosed \\u{ escape".to_string()));
+        }
+        self.pos += 1;
+
+        match u32::from_str_radix(&digits, 16).ok().and_then(std::char::from_u32) {
+            Some(c) => Ok(c),
+            None => Err(self.error_at(start, format!("\\u{{{}}} is not a valid character", digits)))
+        }
+    }
+
+    /// class := '[' '^'? item+ ']'
+    ///
+    /// A `]` straight after the opening bracket (or `^`) is a symbol, as
+    /// is a `-` that does not sit between two symbols. Escaped symbols may
+    /// be used on either side of a range.
+    fn bracket(&mut self) -> Result<Regex, RegexError> {
+
+        let start = self.offset();
+        self.pos += 1;
+
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+
+        let mut class = CharClass::new(vec!());
+        let mut first = true;
+
+        loop {
+            let item_start = self.offset();
+
+            let lo = match self.peek() {
+                None => return Err(self.error_at(start, "Unclosed class".to_string())),
+                Some(']') if !first => break,
+                Some('\\') => {
+                    self.pos += 1;
+                    first = false;
+                    match self.escape(item_start)? {
+                        Regex::Literal(c) => c,
+                        Regex::Class(perl) => {
+                            class = class.union(&perl);
+                            continue;
+                        },
+                        _ => unreachable!()
+                    }
+                },
+                Some(c) => {
+                    self.pos += 1;
+                    first = false;
+                    c
+                }
+            };
+
+            // A range needs a symbol on both sides of the `-`
+            let is_range = self.peek() == Some('-')
+                           && self.chars.get(self.pos + 1).is_some_and(|c| c.1 != ']');
+
+            if !is_range {
+                class = class.union(&CharClass::single(lo));
+                continue;
+            }
+
+            self.pos += 1;
+            let hi_start = self.offset();
+            let hi = match self.peek() {
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.escape(hi_start)? {
+                        Regex::Literal(c) => c,
+                        _ => return Err(self.error_at(hi_start,
+                                 format!("Class escape cannot end the range starting at {}", lo)))
+                    }
+                },
+                Some(c) => {
+                    self.pos += 1;
+                    c
+                },
+                None => return Err(self.error_at(start, "Unclosed class".to_string()))
+            };
+
+            if hi < lo {
+                return Err(self.error_at(hi_start, format!("Range {}-{} is out of order", lo, hi)));
+            }
+            class = class.union(&CharClass::new(vec![(lo, hi)]));
+        }
+        self.pos += 1;
+
+        // Case variants are added before negating, so [^k] with the i flag
+        // matches neither k nor K
+        let class = if self.flags.case_insensitive { class.case_fold() } else { class };
+
+        Ok(Regex::Class(if negated { class.negate() } else { class }))
+    }
+
+}
@@ -0,0 +1,96 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with a set of regexes compiled into
+//! a single DFA.
+//!
+//! The ε-NFAs of the patterns are joined under one start state, each
+//! keeping an accept state of its own, and the whole thing is determinized
+//! once. A DFA state is then tagged with every pattern whose accept state
+//! is in its set, so one pass over a line tells which patterns accept it.
+
+use std::io;
+use std::io::prelude::*;
+
+use crate::charclass;
+use crate::dfa::DFA;
+use crate::nfa::NFA;
+use crate::regex::{self, RegexError, Syntax};
+
+// *********************************************************************
+/// # Regex Set Structure
+pub struct RegexSet {
+
+    /// The patterns in the order they were given
+    patterns: Vec<String>,
+
+    /// The DFA for all of the patterns at once
+    dfa: Box<DFA>,
+
+    /// For each DFA state (0 relative), the sorted indices of the patterns
+    /// accepting when the text ends there
+    accepts: Vec<Vec<usize>>,
+}
+
+// *********************************************************************
+/// Implement the methods of the RegexSet structure
+impl RegexSet {
+
+    /// Parse and compile the patterns, stopping at the first that is
+    /// malformed
+    pub fn new(patterns: &[String], syntax: &Syntax) -> Result<RegexSet, RegexError> {
+
+        let regexes = patterns.iter()
+                              .map(|p| regex::parse(p, syntax))
+                              .collect::<Result<Vec<_>, _>>()?;
+
+        let (nfa, accept_states) = NFA::new_from_regexes(&regexes);
+        let (dfa, sets) = DFA::new_from_nfa_sets(&nfa);
+
+        // Tag each DFA state with the patterns whose accept state it holds
+        let accepts = sets.iter()
+                          .map(|set| {
+                              let matched = nfa.matches(set);
+                              (0..patterns.len()).filter(|i| matched.contains(&accept_states[*i]))
+                                                 .collect()
+                          })
+                          .collect();
+
+        Ok(RegexSet{patterns: patterns.to_vec(), dfa, accepts})
+    }
+
+    /// Number of states in the combined DFA
+    pub fn states(&self) -> usize {
+        self.dfa.transitions.len()
+    }
+
+    /// The indices of the patterns matching the whole text
+    pub fn matches(&self, text: &str) -> &[usize] {
+
+        let mut state = self.dfa.start - 1;
+        for ch in text.chars() {
+            state = self.dfa.transitions[state][charclass::column(&self.dfa.alphabet, ch)] - 1;
+        }
+
+        &self.accepts[state]
+    }
+
+    /// Print which patterns accept each line of stdin
+    pub fn process(&self) {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+
+            // Get the line out of the Result, should never error
+            let sentence = &line.unwrap();
+            println!("Processing sentence <{}>", sentence);
+
+            let matched = self.matches(sentence);
+            if matched.is_empty() {
+                println!("No pattern accepts");
+            }
+            for i in matched {
+                println!("Pattern {} <{}> accepts", i, self.patterns[*i]);
+            }
+        }
+    }
+
+}
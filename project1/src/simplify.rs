@@ -0,0 +1,356 @@
+//! CSIS-616 - Project #1
+//!
+//! Rewrite a regex into a smaller one matching the same strings.
+//!
+//! The syntax tree is rebuilt bottom up through constructors that apply
+//! algebraic laws, each of which holds for every regex, so the language
+//! never changes:
+//!
+//! - idempotence: `r|r = r`, and `r|s = s` whenever `s` plainly matches
+//!   everything `r` does, as in `a|ab* = ab*` or `a|a* = a*`
+//! - single symbols: `a|b|[c-e] = [a-e]`
+//! - factoring: `ab|ac = a(b|c)` and `ac|bc = (a|b)c`
+//! - star collapse: `(r*)* = r*`, `(r?)* = r*`, `(r|s?)* = (r|s)*` and
+//!   `(r*s*)* = (r|s)*`
+//! - repetition: `rr* = r*r = r+`, `r*r* = r*`, `(r+)? = r*`, `r? = r`
+//!   when `r` matches the empty string, and counts of 0 or 1
+//! - units: `rε = r`, and groups are dropped
+//! - nothing: `r∅ = ∅`, `r|∅ = r` and `∅* = ε`, ∅ being a class with no
+//!   characters
+//!
+//! The rewriting is repeated until nothing changes. Capture groups are not
+//! kept, only the strings matched.
+
+use crate::charclass::CharClass;
+use crate::regex::Regex;
+
+// *********************************************************************
+/// A regex matching the same strings with at most as many symbols
+pub fn simplify(regex: &Regex) -> Regex {
+
+    let mut current = regex.clone();
+    loop {
+        let next = rewrite(&current);
+        if next == current {
+            break;
+        }
+        current = next;
+    }
+
+    if current.size() <= regex.size() { current } else { regex.clone() }
+}
+
+// *********************************************************************
+/// One pass of the laws over the tree, children first
+fn rewrite(regex: &Regex) -> Regex {
+    match regex {
+        Regex::Class(class) => match class.ranges() {
+            [(lo, hi)] if lo == hi => Regex::Literal(*lo),
+            _ => regex.clone()
+        },
+        Regex::Literal(_) | Regex::Look(_) => regex.clone(),
+        Regex::Group(_, inner) => rewrite(inner),
+        Regex::Concat(items) => concat(items.iter().map(rewrite).collect()),
+        Regex::Alternation(items) => alternation(items.iter().map(rewrite).collect()),
+        Regex::Star(inner) => star(rewrite(inner)),
+        Regex::Plus(inner) => plus(rewrite(inner)),
+        Regex::Optional(inner) => optional(rewrite(inner)),
+        Regex::Repeat(inner, min, max) => repeat(rewrite(inner), *min, *max)
+    }
+}
+
+// *********************************************************************
+/// The regex matching only the empty string
+fn epsilon() -> Regex {
+    Regex::Concat(vec!())
+}
+
+// *********************************************************************
+/// Is the regex the one matching only the empty string
+fn is_epsilon(regex: &Regex) -> bool {
+    matches!(regex, Regex::Concat(items) if items.is_empty())
+}
+
+// *********************************************************************
+/// The regex matching nothing
+fn nothing() -> Regex {
+    Regex::Class(CharClass::new(vec!()))
+}
+
+// *********************************************************************
+/// Is the regex the one matching nothing
+fn is_nothing(regex: &Regex) -> bool {
+    matches!(regex, Regex::Class(class) if class.ranges().is_empty())
+}
+
+// *********************************************************************
+/// Does the regex match the empty string everywhere, anchors are taken
+/// to never match
+fn nullable(regex: &Regex) -> bool {
+    match regex {
+        Regex::Literal(_) | Regex::Class(_) | Regex::Look(_) => false,
+        Regex::Star(_) | Regex::Optional(_) => true,
+        Regex::Concat(items) => items.iter().all(nullable),
+        Regex::Alternation(items) => items.iter().any(nullable),
+        Regex::Plus(inner) | Regex::Group(_, inner) => nullable(inner),
+        Regex::Repeat(inner, min, _) => *min == 0 || nullable(inner)
+    }
+}
+
+// *********************************************************************
+/// The items of a concatenation, or the regex alone
+fn sequence(regex: &Regex) -> Vec<Regex> {
+    match regex {
+        Regex::Concat(items) => items.clone(),
+        r => vec![r.clone()]
+    }
+}
+
+// *********************************************************************
+/// Does `big` plainly match every string `small` does
+///
+/// False does not mean it doesn't, only that none of the simple cases
+/// checked here applies.
+fn subsumes(big: &Regex, small: &Regex) -> bool {
+
+    if big == small || (is_epsilon(small) && nullable(big)) {
+        return true;
+    }
+
+    match (big, small) {
+        (Regex::Star(x), Regex::Star(y)) | (Regex::Star(x), Regex::Plus(y))
+            | (Regex::Star(x), Regex::Optional(y)) | (Regex::Plus(x), Regex::Plus(y)) => subsumes(x, y),
+        (Regex::Star(x), _) | (Regex::Plus(x), _) | (Regex::Optional(x), _) => subsumes(x, small),
+        (Regex::Class(c), Regex::Literal(ch)) => c.contains(*ch),
+        (Regex::Class(c), Regex::Class(d)) => c.union(d) == *c,
+        (Regex::Alternation(items), _) => items.iter().any(|r| subsumes(r, small)),
+        (Regex::Concat(items), _) => {
+
+            // `small` followed or preceded by things matching the empty string
+            let part = sequence(small);
+            items.len() > part.len()
+                && ((items[..part.len()] == part[..] && items[part.len()..].iter().all(nullable))
+                    || (items[items.len() - part.len()..] == part[..]
+                        && items[..items.len() - part.len()].iter().all(nullable)))
+        },
+        _ => false
+    }
+}
+
+// *********************************************************************
+/// The items one after the other
+fn concat(items: Vec<Regex>) -> Regex {
+
+    if items.iter().any(is_nothing) {
+        return nothing();
+    }
+
+    let mut result: Vec<Regex> = Vec::new();
+
+    for item in items {
+        for r in sequence(&item) {
+
+            let merged = match (result.last(), &r) {
+                (Some(Regex::Star(x)), Regex::Star(y)) | (Some(Regex::Optional(x)), Regex::Star(y))
+                    | (Some(Regex::Star(x)), Regex::Optional(y)) if x == y => Some(Regex::Star(x.clone())),
+                (Some(Regex::Star(x)), Regex::Plus(y)) | (Some(Regex::Plus(x)), Regex::Star(y)) if x == y => {
+                    Some(Regex::Plus(x.clone()))
+                },
+                (Some(x), Regex::Star(y)) | (Some(Regex::Star(y)), x) if **y == *x => Some(Regex::Plus(y.clone())),
+                _ => None
+            };
+
+            match merged {
+                Some(m) => *result.last_mut().unwrap() = m,
+                None => result.push(r)
+            }
+        }
+    }
+
+    match result.len() {
+        1 => result.pop().unwrap(),
+        _ => Regex::Concat(result)
+    }
+}
+
+// *********************************************************************
+/// Any one of the items
+fn alternation(items: Vec<Regex>) -> Regex {
+
+    // Flatten, merge single symbols into the first class and drop duplicates
+    let mut flat: Vec<Regex> = Vec::new();
+    let mut class: Option<usize> = None;
+    let mut pending = items;
+    pending.reverse();
+    while let Some(item) = pending.pop() {
+        let symbols = match item {
+            Regex::Alternation(inner) => {
+                pending.extend(inner.into_iter().rev());
+                continue;
+            },
+            Regex::Literal(c) => CharClass::single(c),
+            Regex::Class(c) => c,
+            r => {
+                if !flat.contains(&r) {
+                    flat.push(r);
+                }
+                continue;
+            }
+        };
+        match class {
+            Some(at) => {
+                if let Regex::Class(c) = &flat[at] {
+                    flat[at] = Regex::Class(c.union(&symbols));
+                }
+            },
+            None => {
+                class = Some(flat.len());
+                flat.push(Regex::Class(symbols));
+            }
+        }
+    }
+    if let Some(at) = class {
+        flat[at] = rewrite(&flat[at]);
+    }
+
+    // Nothing adds no strings, and is all that is left with no other item
+    if flat.iter().all(is_nothing) {
+        return nothing();
+    }
+    flat.retain(|r| !is_nothing(r));
+
+    // Drop the items another one left already matches
+    let mut n = 0;
+    while n < flat.len() {
+        if (0..flat.len()).any(|m| m != n && subsumes(&flat[m], &flat[n])) {
+            flat.remove(n);
+        } else {
+            n += 1;
+        }
+    }
+
+    let flat = factor(flat, true);
+    let mut flat = factor(flat, false);
+
+    // What is left of the empty string becomes a ?
+    let optional_items = flat.iter().any(is_epsilon);
+    flat.retain(|r| !is_epsilon(r));
+    let rest = match flat.len() {
+        0 => epsilon(),
+        1 => flat.pop().unwrap(),
+        _ => Regex::Alternation(flat)
+    };
+
+    if optional_items { optional(rest) } else { rest }
+}
+
+// *********************************************************************
+/// Join the items starting (`front`) or ending with the same item,
+/// `ab|ac` becoming `a(b|c)`, keeping the place of the first of them
+fn factor(items: Vec<Regex>, front: bool) -> Vec<Regex> {
+
+    // The item at the end of each, and what is left without it
+    let ends: Vec<Option<(Regex, Vec<Regex>)>> = items.iter().map(|r| {
+        let mut seq = sequence(r);
+        if seq.is_empty() {
+            return None;
+        }
+        let end = if front { seq.remove(0) } else { seq.pop().unwrap() };
+        Some((end, seq))
+    }).collect();
+
+    let mut result: Vec<Regex> = Vec::new();
+    let mut used = vec![false; items.len()];
+
+    for n in 0..items.len() {
+        if used[n] {
+            continue;
+        }
+
+        let end = match &ends[n] {
+            Some((end, _)) => end,
+            None => {
+                result.push(items[n].clone());
+                used[n] = true;
+                continue;
+            }
+        };
+        let shared: Vec<usize> = (n..items.len())
+            .filter(|m| !used[*m] && ends[*m].as_ref().is_some_and(|(e, _)| e == end))
+            .collect();
+        if shared.len() == 1 {
+            result.push(items[n].clone());
+            used[n] = true;
+            continue;
+        }
+
+        let rests: Vec<Regex> = shared.iter().map(|m| concat(ends[*m].as_ref().unwrap().1.clone())).collect();
+        for m in shared {
+            used[m] = true;
+        }
+        let rest = alternation(rests);
+        result.push(if front { concat(vec![end.clone(), rest]) } else { concat(vec![rest, end.clone()]) });
+    }
+
+    result
+}
+
+// *********************************************************************
+/// Zero or more repetitions
+fn star(regex: Regex) -> Regex {
+    match regex {
+        r if is_epsilon(&r) || is_nothing(&r) => epsilon(),
+        Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) => star(*inner),
+
+        // Inside a star, the parts that may match the empty string only need
+        // to match something
+        Regex::Alternation(items) if items.iter().any(|r| matches!(r, Regex::Star(_) | Regex::Plus(_) | Regex::Optional(_))) => {
+            let items = items.into_iter().map(|r| match r {
+                Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) => *inner,
+                r => r
+            }).collect();
+            star(alternation(items))
+        },
+        Regex::Concat(items) if items.iter().all(nullable) => star(alternation(items)),
+        Regex::Repeat(inner, min, max) if min <= 1 && max != Some(0) => star(*inner),
+        r => Regex::Star(Box::new(r))
+    }
+}
+
+// *********************************************************************
+/// One or more repetitions
+fn plus(regex: Regex) -> Regex {
+    match regex {
+        r if nullable(&r) => star(r),
+        r if is_nothing(&r) => r,
+        Regex::Plus(inner) => Regex::Plus(inner),
+        r => Regex::Plus(Box::new(r))
+    }
+}
+
+// *********************************************************************
+/// Zero or one occurrence
+fn optional(regex: Regex) -> Regex {
+    match regex {
+        r if nullable(&r) => r,
+        r if is_nothing(&r) => epsilon(),
+        Regex::Plus(inner) => Regex::Star(inner),
+        r => Regex::Optional(Box::new(r))
+    }
+}
+
+// *********************************************************************
+/// Between `min` and `max` occurrences
+fn repeat(regex: Regex, min: usize, max: Option<usize>) -> Regex {
+    match (min, max) {
+        _ if is_epsilon(&regex) => regex,
+        (_, Some(0)) => epsilon(),
+        (0, _) if is_nothing(&regex) => epsilon(),
+        _ if is_nothing(&regex) => regex,
+        (0, None) => star(regex),
+        (1, None) => plus(regex),
+        (0, Some(1)) => optional(regex),
+        (1, Some(1)) => regex,
+        _ => Regex::Repeat(Box::new(regex), min, max)
+    }
+}
@@ -0,0 +1,143 @@
+//! CSIS-616 - Project #1
+//!
+//! Definition and methods associated with a matcher fed from a stream of
+//! bytes.
+//!
+//! The matcher holds the current state of a byte DFA between calls, so the
+//! input can arrive in chunks of any size, split anywhere, even inside a
+//! character, and be scanned without ever holding more than one chunk in
+//! memory. Bytes go straight through the byte DFA without being decoded;
+//! a byte sequence that is not valid UTF-8 reads as U+FFFD replacement
+//! characters, the same as `String::from_utf8_lossy`.
+//!
+//! Run on the DFA of the regex, the matcher tells whether the input read
+//! so far matches. Run on the unanchored DFA from `DFA::new_unanchored`, it
+//! reports the end of every match anywhere in the input.
+
+use std::io;
+use std::io::prelude::*;
+
+use crate::bytedfa::ByteDFA;
+
+// *********************************************************************
+/// Number of bytes read from the stream at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// *********************************************************************
+/// # Stream Matcher Structure
+pub struct Matcher<'a> {
+
+    /// The DFA being run, it must not use `^` or `$`
+    dfa: &'a ByteDFA,
+
+    /// The current state (0 relative)
+    state: usize,
+
+    /// Number of bytes of the stream read so far
+    offset: usize,
+
+    /// Has the empty input at offset 0 been checked yet
+    started: bool,
+}
+
+// *********************************************************************
+/// Implement the methods of the Matcher structure
+impl<'a> Matcher<'a> {
+
+    /// A matcher at the start of the stream
+    pub fn new(dfa: &'a ByteDFA) -> Matcher<'a> {
+        Matcher{dfa, state: dfa.start, offset: 0, started: false}
+    }
+
+    /// Number of bytes of the stream read so far
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Does the input read so far match, reading a character still waiting
+    /// for the rest of its bytes as U+FFFD
+    pub fn is_match(&self) -> bool {
+        self.dfa.accept[self.state]
+    }
+
+    /// Read the next chunk of the stream, returning the byte offsets
+    /// within the stream at which the DFA entered an accept state
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<usize> {
+
+        let mut ends = self.start();
+
+        for b in chunk {
+
+            // A character cut short by this byte is a replacement character
+            // ending here
+            if let Some((lo, hi, _)) = self.dfa.partial[self.state] {
+                if (*b < lo || *b > hi) && self.is_match() {
+                    ends.push(self.offset);
+                }
+            }
+
+            self.state = self.dfa.transitions[self.state][*b as usize];
+            self.offset += 1;
+            if self.state < self.dfa.characters && self.is_match() {
+                ends.push(self.offset);
+            }
+        }
+
+        ends
+    }
+
+    /// The stream has ended, a character cut short at the end is read as
+    /// U+FFFD
+    pub fn finish(&mut self) -> Vec<usize> {
+
+        let mut ends = self.start();
+
+        if let Some((_, _, fallback)) = self.dfa.partial[self.state] {
+            if self.is_match() {
+                ends.push(self.offset);
+            }
+            self.state = fallback;
+        }
+
+        ends
+    }
+
+    /// Read the whole stream in chunks, calling `found` with the offset of
+    /// each accept state reached, and stopping at the first error from
+    /// either the stream or `found`
+    pub fn scan<R, F>(&mut self, mut reader: R, mut found: F) -> io::Result<()>
+        where R: Read,
+              F: FnMut(usize) -> io::Result<()> {
+
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e)
+            };
+            for end in self.feed(&buffer[..n]) {
+                found(end)?;
+            }
+        }
+
+        for end in self.finish() {
+            found(end)?;
+        }
+
+        Ok(())
+    }
+
+    /// Offset 0 when the start state accepts the empty input, the first
+    /// time the stream is read
+    fn start(&mut self) -> Vec<usize> {
+        if self.started {
+            return vec!();
+        }
+        self.started = true;
+        if self.is_match() { vec![0] } else { vec!() }
+    }
+
+}